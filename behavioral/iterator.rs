@@ -17,7 +17,7 @@ struct ConcreteIterator<'a, T> {
 }
 
 impl<'a, T: Clone> ConcreteIterator<'a, T> {
-    fn new(container: &'a Container<T>) -> ConcreteIterator<T> {
+    fn new(container: &'a Container<T>) -> ConcreteIterator<'a, T> {
         ConcreteIterator { idx: 0, container }
     }
 }
@@ -59,9 +59,10 @@ fn main() {
 
     let mut iter = c.iter();
     let has_next = iter.has_next();
-    assert_eq!(has_next, true);
+    assert!(has_next);
     let item = iter.next();
     println!("item: {:?}", item);
+    assert_eq!(iter.current(), Some(2));
     iter.reset();
     while iter.has_next() {
         let v = iter.next().unwrap();
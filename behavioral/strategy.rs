@@ -32,7 +32,7 @@ struct MallardDuck {
 
 impl Duck for MallardDuck {
     fn get_fly_behaviour(&self) -> &dyn FlyBehaviour {
-        return &(*self.fly_behaviour);
+        &(*self.fly_behaviour)
     }
 }
 
@@ -51,7 +51,7 @@ struct ModelDuck {
 
 impl Duck for ModelDuck {
     fn get_fly_behaviour(&self) -> &dyn FlyBehaviour {
-        return &(*self.fly_behaviour);
+        &(*self.fly_behaviour)
     }
 }
 
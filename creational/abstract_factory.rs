@@ -1,87 +1,912 @@
 //! Abstract Factory is a creational design pattern that lets you produce families of related objects without specifying their concrete classes.
 
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::env;
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::process;
+use std::rc::Rc;
+
+/// Implements `Widget` and the trait of its kind for the six widgets of a
+/// family, given in the order of `WidgetFamily`. Each widget keeps its model
+/// in field `0` and has an inherent `paint`, the only part that differs
+/// between families; the rest delegates to the model.
+macro_rules! family_widgets {
+    ($button:ty, $checkbox:ty, $text_field:ty, $slider:ty, $menu:ty, $dialog:ty) => {
+        impl $crate::Widget for $button {
+            fn paint(&self, out: &mut dyn std::fmt::Write) -> std::fmt::Result {
+                Self::paint(self, out)
+            }
+            fn handle_event(&mut self, event: &$crate::Event) -> bool {
+                self.0.handle_event(event)
+            }
+        }
+        impl $crate::Button for $button {
+            fn model_mut(&mut self) -> &mut $crate::ButtonModel {
+                &mut self.0
+            }
+        }
+
+        impl $crate::Widget for $checkbox {
+            fn paint(&self, out: &mut dyn std::fmt::Write) -> std::fmt::Result {
+                Self::paint(self, out)
+            }
+            fn handle_event(&mut self, event: &$crate::Event) -> bool {
+                self.0.handle_event(event)
+            }
+            fn state(&self) -> Option<$crate::WidgetState> {
+                Some($crate::WidgetState::Checked(self.0.checked))
+            }
+        }
+        impl $crate::Checkbox for $checkbox {
+            fn model(&self) -> &$crate::CheckboxModel {
+                &self.0
+            }
+            fn model_mut(&mut self) -> &mut $crate::CheckboxModel {
+                &mut self.0
+            }
+        }
+
+        impl $crate::Widget for $text_field {
+            fn paint(&self, out: &mut dyn std::fmt::Write) -> std::fmt::Result {
+                Self::paint(self, out)
+            }
+            fn handle_event(&mut self, event: &$crate::Event) -> bool {
+                self.0.handle_event(event)
+            }
+            fn state(&self) -> Option<$crate::WidgetState> {
+                Some($crate::WidgetState::Text(self.0.text.clone()))
+            }
+        }
+        impl $crate::TextField for $text_field {
+            fn model(&self) -> &$crate::TextFieldModel {
+                &self.0
+            }
+        }
+
+        impl $crate::Widget for $slider {
+            fn paint(&self, out: &mut dyn std::fmt::Write) -> std::fmt::Result {
+                Self::paint(self, out)
+            }
+            fn handle_event(&mut self, event: &$crate::Event) -> bool {
+                self.0.handle_event(event)
+            }
+            fn state(&self) -> Option<$crate::WidgetState> {
+                Some($crate::WidgetState::Value(self.0.value))
+            }
+        }
+        impl $crate::Slider for $slider {
+            fn model(&self) -> &$crate::SliderModel {
+                &self.0
+            }
+        }
+
+        impl $crate::Widget for $menu {
+            fn paint(&self, out: &mut dyn std::fmt::Write) -> std::fmt::Result {
+                Self::paint(self, out)
+            }
+        }
+        impl $crate::Menu for $menu {}
+
+        impl $crate::Widget for $dialog {
+            fn paint(&self, out: &mut dyn std::fmt::Write) -> std::fmt::Result {
+                Self::paint(self, out)
+            }
+        }
+        impl $crate::Dialog for $dialog {}
+    };
+}
+
+#[path = "abstract_factory/events.rs"]
+mod events;
+#[path = "abstract_factory/layout.rs"]
+mod layout;
+#[path = "abstract_factory/mac.rs"]
+mod mac;
+#[path = "abstract_factory/snapshot.rs"]
+mod snapshot;
+#[path = "abstract_factory/text.rs"]
+mod text;
+#[path = "abstract_factory/theme.rs"]
+mod theme;
+#[path = "abstract_factory/view.rs"]
+mod view;
+#[path = "abstract_factory/win.rs"]
+mod win;
+
+use events::{Event, EventLoop, Input, Key};
+use layout::{Layout, Metrics, Size, Window};
+use mac::MacFactory;
+use snapshot::Snapshots;
+use text::TextFactory;
+use theme::{ThemeError, ThemeFactory, ThemeLibrary};
+use view::{Ui, View, WidgetState};
+use win::WinFactory;
+
 trait GUIFactory {
-    fn create_button(&self) -> Box<dyn Button>;
-    fn create_checkbox(&self) -> Box<dyn Checkbox>;
+    fn create_button(&self, label: &str) -> Box<dyn Button>;
+    fn create_checkbox(&self, label: &str, checked: bool) -> Box<dyn Checkbox>;
+    fn create_text_field(&self, label: &str, text: &str) -> Box<dyn TextField>;
+    fn create_slider(&self, label: &str, min: f64, max: f64, value: f64) -> Box<dyn Slider>;
+    fn create_menu(&self, title: &str, items: &[&str]) -> Box<dyn Menu>;
+    fn create_dialog(&self, title: &str, message: &str) -> Box<dyn Dialog>;
+    fn metrics(&self) -> Metrics;
 }
 
-struct WinFactory;
-impl GUIFactory for WinFactory {
-    fn create_button(&self) -> Box<dyn Button> {
-        Box::new(WinButton {})
+/// A family of widgets tied together through associated types. Code generic
+/// over `F: WidgetFamily` can only combine widgets of the same family, so a
+/// `MacButton` never ends up next to a `WinCheckbox`. Every family is also a
+/// `GUIFactory` for code that picks the theme at runtime.
+trait WidgetFamily {
+    type Button: Button + 'static;
+    type Checkbox: Checkbox + 'static;
+    type TextField: TextField + 'static;
+    type Slider: Slider + 'static;
+    type Menu: Menu + 'static;
+    type Dialog: Dialog + 'static;
+
+    fn button(&self, model: ButtonModel) -> Self::Button;
+    fn checkbox(&self, model: CheckboxModel) -> Self::Checkbox;
+    fn text_field(&self, model: TextFieldModel) -> Self::TextField;
+    fn slider(&self, model: SliderModel) -> Self::Slider;
+    fn menu(&self, model: MenuModel) -> Self::Menu;
+    fn dialog(&self, model: DialogModel) -> Self::Dialog;
+    fn metrics(&self) -> Metrics;
+}
+
+impl<F: WidgetFamily> GUIFactory for F {
+    fn create_button(&self, label: &str) -> Box<dyn Button> {
+        Box::new(self.button(ButtonModel::new(label)))
+    }
+    fn create_checkbox(&self, label: &str, checked: bool) -> Box<dyn Checkbox> {
+        Box::new(self.checkbox(CheckboxModel::new(label, checked)))
+    }
+    fn create_text_field(&self, label: &str, text: &str) -> Box<dyn TextField> {
+        Box::new(self.text_field(TextFieldModel::new(label, text)))
+    }
+    fn create_slider(&self, label: &str, min: f64, max: f64, value: f64) -> Box<dyn Slider> {
+        Box::new(self.slider(SliderModel::new(label, min, max, value)))
+    }
+    fn create_menu(&self, title: &str, items: &[&str]) -> Box<dyn Menu> {
+        Box::new(self.menu(MenuModel::new(title, items)))
+    }
+    fn create_dialog(&self, title: &str, message: &str) -> Box<dyn Dialog> {
+        Box::new(self.dialog(DialogModel::new(title, message)))
     }
-    fn create_checkbox(&self) -> Box<dyn Checkbox> {
-        Box::new(WinCheckbox {})
+    fn metrics(&self) -> Metrics {
+        WidgetFamily::metrics(self)
     }
 }
 
-struct MacFactory;
-impl GUIFactory for MacFactory {
-    fn create_button(&self) -> Box<dyn Button> {
-        Box::new(MacButton {})
+// The data and behaviour behind each kind of widget, shared by all themes.
+// Themes only decide how it is painted.
+
+struct ButtonModel {
+    label: String,
+    on_click: Vec<Box<dyn FnMut()>>,
+}
+
+impl ButtonModel {
+    fn new(label: &str) -> ButtonModel {
+        ButtonModel {
+            label: label.to_string(),
+            on_click: Vec::new(),
+        }
     }
-    fn create_checkbox(&self) -> Box<dyn Checkbox> {
-        Box::new(MacCheckbox {})
+    fn handle_event(&mut self, event: &Event) -> bool {
+        match event {
+            Event::Click | Event::Key(Key::Enter) | Event::Key(Key::Char(' ')) => {
+                for callback in &mut self.on_click {
+                    callback();
+                }
+                true
+            }
+            _ => false,
+        }
     }
 }
 
-trait Button {
-    fn paint(&self);
+struct CheckboxModel {
+    label: String,
+    checked: bool,
+    on_toggle: Vec<Box<dyn FnMut(bool)>>,
 }
 
-struct WinButton;
-impl Button for WinButton {
-    fn paint(&self) {
-        println!("windows os button");
+impl CheckboxModel {
+    fn new(label: &str, checked: bool) -> CheckboxModel {
+        CheckboxModel {
+            label: label.to_string(),
+            checked,
+            on_toggle: Vec::new(),
+        }
+    }
+    fn set_checked(&mut self, checked: bool) {
+        if self.checked != checked {
+            self.checked = checked;
+            for callback in &mut self.on_toggle {
+                callback(checked);
+            }
+        }
     }
+    fn handle_event(&mut self, event: &Event) -> bool {
+        match event {
+            Event::Click | Event::Key(Key::Char(' ')) => {
+                self.set_checked(!self.checked);
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+struct TextFieldModel {
+    label: String,
+    text: String,
+}
+
+impl TextFieldModel {
+    fn new(label: &str, text: &str) -> TextFieldModel {
+        TextFieldModel {
+            label: label.to_string(),
+            text: text.to_string(),
+        }
+    }
+    fn handle_event(&mut self, event: &Event) -> bool {
+        match event {
+            Event::Key(Key::Char(c)) => self.text.push(*c),
+            Event::Key(Key::Backspace) => {
+                self.text.pop();
+            }
+            _ => return false,
+        }
+        true
+    }
+}
+
+struct SliderModel {
+    label: String,
+    min: f64,
+    max: f64,
+    value: f64,
+}
+
+impl SliderModel {
+    fn new(label: &str, min: f64, max: f64, value: f64) -> SliderModel {
+        SliderModel {
+            label: label.to_string(),
+            min,
+            max,
+            value: value.max(min).min(max),
+        }
+    }
+    /// The arrow keys move the slider by a tenth of its range.
+    fn handle_event(&mut self, event: &Event) -> bool {
+        let step = (self.max - self.min) / 10.0;
+        let value = match event {
+            Event::Key(Key::Left) => self.value - step,
+            Event::Key(Key::Right) => self.value + step,
+            _ => return false,
+        };
+        self.value = value.max(self.min).min(self.max);
+        true
+    }
+}
+
+struct MenuModel {
+    title: String,
+    items: Vec<String>,
 }
 
-struct MacButton;
-impl Button for MacButton {
-    fn paint(&self) {
-        println!("mac os button");
+impl MenuModel {
+    fn new(title: &str, items: &[&str]) -> MenuModel {
+        MenuModel {
+            title: title.to_string(),
+            items: items.iter().map(|item| item.to_string()).collect(),
+        }
     }
 }
 
-trait Checkbox {
-    fn paint(&self);
+struct DialogModel {
+    title: String,
+    message: String,
+}
+
+impl DialogModel {
+    fn new(title: &str, message: &str) -> DialogModel {
+        DialogModel {
+            title: title.to_string(),
+            message: message.to_string(),
+        }
+    }
+}
+
+/// What every widget can do, whatever its kind. Widgets paint into a
+/// caller-supplied sink, e.g. a `String`.
+trait Widget {
+    fn paint(&self, out: &mut dyn fmt::Write) -> fmt::Result;
+    /// Returns whether the widget reacted to the event.
+    fn handle_event(&mut self, _event: &Event) -> bool {
+        false
+    }
+    /// The state a user can change, for widgets that have one.
+    fn state(&self) -> Option<WidgetState> {
+        None
+    }
+    /// The size of the painted text in the theme's units. Color escapes take
+    /// no room.
+    fn preferred_size(&self, metrics: &Metrics) -> Size {
+        let mut text = String::new();
+        let _ = self.paint(&mut text);
+        let columns = text.lines().map(visible_width).max().unwrap_or(0);
+        let lines = text.lines().count().max(1);
+        Size {
+            width: columns as u32 * metrics.char_width,
+            height: lines as u32 * metrics.line_height,
+        }
+    }
 }
 
-struct WinCheckbox;
-impl Checkbox for WinCheckbox {
-    fn paint(&self) {
-        println!("windows os checkbox");
+/// The number of characters of `line` shown on a terminal, leaving out ANSI
+/// `ESC [ ... m` sequences.
+fn visible_width(line: &str) -> usize {
+    let mut width = 0;
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        if c == '\x1b' {
+            chars.by_ref().find(|&c| c == 'm');
+        } else {
+            width += 1;
+        }
     }
+    width
 }
 
-struct MacCheckbox;
-impl Checkbox for MacCheckbox {
-    fn paint(&self) {
-        println!("mac os checkbox");
+trait Button: Widget {
+    fn model_mut(&mut self) -> &mut ButtonModel;
+
+    fn on_click(&mut self, callback: Box<dyn FnMut()>) {
+        self.model_mut().on_click.push(callback);
     }
 }
 
-struct Application;
+trait Checkbox: Widget {
+    fn model(&self) -> &CheckboxModel;
+    fn model_mut(&mut self) -> &mut CheckboxModel;
+
+    fn is_checked(&self) -> bool {
+        self.model().checked
+    }
+    fn set_checked(&mut self, checked: bool) {
+        self.model_mut().set_checked(checked);
+    }
+    /// Called with the new state every time the checkbox is toggled.
+    fn on_toggle(&mut self, callback: Box<dyn FnMut(bool)>) {
+        self.model_mut().on_toggle.push(callback);
+    }
+}
+
+trait TextField: Widget {
+    fn model(&self) -> &TextFieldModel;
+
+    fn text(&self) -> &str {
+        &self.model().text
+    }
+}
+
+trait Slider: Widget {
+    fn model(&self) -> &SliderModel;
+
+    fn value(&self) -> f64 {
+        self.model().value
+    }
+}
+
+trait Menu: Widget {}
+
+trait Dialog: Widget {}
+
+/// A form built from a single family, checked at compile time.
+struct LoginForm<F: WidgetFamily> {
+    user: F::TextField,
+    remember: F::Checkbox,
+    submit: F::Button,
+}
+
+impl<F: WidgetFamily> LoginForm<F> {
+    fn new(family: &F) -> LoginForm<F> {
+        LoginForm {
+            user: family.text_field(TextFieldModel::new("User", "")),
+            remember: family.checkbox(CheckboxModel::new("Remember me", false)),
+            submit: family.button(ButtonModel::new("Log in")),
+        }
+    }
+
+    fn paint(&self, out: &mut dyn fmt::Write) -> fmt::Result {
+        self.user.paint(out)?;
+        writeln!(out)?;
+        self.remember.paint(out)?;
+        writeln!(out)?;
+        self.submit.paint(out)?;
+        writeln!(out)
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum GuiError {
+    UnknownTheme { name: String, known: Vec<String> },
+}
+
+impl fmt::Display for GuiError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            GuiError::UnknownTheme { name, known } => write!(
+                f,
+                "unknown theme `{}`, known themes: {}",
+                name,
+                known.join(", ")
+            ),
+        }
+    }
+}
+
+impl Error for GuiError {}
+
+type FactoryConstructor = Box<dyn Fn() -> Box<dyn GUIFactory>>;
+
+/// Keeps the GUI factories keyed by theme name, so other code can register
+/// its own families next to the built-in ones.
+struct Application {
+    factories: BTreeMap<String, FactoryConstructor>,
+    fallback: Option<String>,
+}
+
 impl Application {
-    fn new_gui_factory(os: &str) -> Box<dyn GUIFactory> {
-        match os {
-            "mac" => Box::new(MacFactory {}),
-            "win" => Box::new(WinFactory {}),
-            _ => panic!("error"),
+    fn new() -> Application {
+        let mut app = Application {
+            factories: BTreeMap::new(),
+            fallback: None,
+        };
+        app.register("mac", || Box::new(MacFactory {}));
+        app.register("text", || Box::new(TextFactory {}));
+        app.register("win", || Box::new(WinFactory {}));
+        app
+    }
+
+    fn register<F>(&mut self, name: &str, constructor: F)
+    where
+        F: Fn() -> Box<dyn GUIFactory> + 'static,
+    {
+        self.factories
+            .insert(name.to_string(), Box::new(constructor));
+    }
+
+    /// Registers every theme of the library, returning their names.
+    fn register_themes(&mut self, library: &ThemeLibrary) -> Result<Vec<String>, ThemeError> {
+        let mut names = Vec::new();
+        for name in library.names() {
+            let theme = Rc::new(library.resolve(name)?);
+            self.register(name, move || Box::new(ThemeFactory::new(theme.clone())));
+            names.push(name.to_string());
+        }
+        Ok(names)
+    }
+
+    /// Rebuilds `ui` with the `name` theme, keeping the state of its widgets.
+    fn switch_theme(&self, ui: &mut Ui, name: &str) -> Result<(), GuiError> {
+        ui.set_factory(self.new_gui_factory(name)?);
+        Ok(())
+    }
+
+    fn themes(&self) -> impl Iterator<Item = &str> {
+        self.factories.keys().map(|name| name.as_str())
+    }
+
+    /// Makes unknown theme names resolve to the `name` theme instead of failing.
+    fn set_fallback(&mut self, name: &str) -> Result<(), GuiError> {
+        if !self.factories.contains_key(name) {
+            return Err(self.unknown(name));
+        }
+        self.fallback = Some(name.to_string());
+        Ok(())
+    }
+
+    fn new_gui_factory(&self, os: &str) -> Result<Box<dyn GUIFactory>, GuiError> {
+        self.factories
+            .get(os)
+            .or_else(|| self.factories.get(self.fallback.as_ref()?))
+            .map(|constructor| constructor())
+            .ok_or_else(|| self.unknown(os))
+    }
+
+    fn unknown(&self, name: &str) -> GuiError {
+        GuiError::UnknownTheme {
+            name: name.to_string(),
+            known: self.factories.keys().cloned().collect(),
         }
     }
 }
 
 fn main() {
-    let mac_app = Application::new_gui_factory("mac");
-    let btn = mac_app.create_button();
-    btn.paint(); // output: mac os button
-    let cb = mac_app.create_checkbox();
-    cb.paint(); // output: mac os checkbox
-
-    let win_app = Application::new_gui_factory("win");
-    let btn = win_app.create_button();
-    btn.paint(); // output: windows os button
-    let cb = win_app.create_checkbox();
-    cb.paint(); // output: windows os checkbox
+    let mut app = Application::new();
+    let mut out = String::new();
+
+    let mac_app = app.new_gui_factory("mac").unwrap();
+    let btn = mac_app.create_button("OK");
+    btn.paint(&mut out).unwrap();
+    println!("{}", out); // output: mac os button: OK
+    let cb = mac_app.create_checkbox("Remember me", true);
+    out.clear();
+    cb.paint(&mut out).unwrap();
+    println!("{}", out); // output: mac os checkbox: Remember me (on)
+
+    let win_app = app.new_gui_factory("win").unwrap();
+    let btn = win_app.create_button("OK");
+    out.clear();
+    btn.paint(&mut out).unwrap();
+    println!("{}", out); // output: windows os button: OK
+    let cb = win_app.create_checkbox("Remember me", false);
+    out.clear();
+    cb.paint(&mut out).unwrap();
+    println!("{}", out); // output: windows os checkbox: Remember me (off)
+
+    let text_app = app.new_gui_factory("text").unwrap();
+    out.clear();
+    text_app.create_button("OK").paint(&mut out).unwrap();
+    assert_eq!(out, "[ OK ]");
+    out.clear();
+    text_app
+        .create_checkbox("Remember me", true)
+        .paint(&mut out)
+        .unwrap();
+    assert_eq!(out, "[x] Remember me");
+
+    out.clear();
+    text_app
+        .create_text_field("Name", "alice")
+        .paint(&mut out)
+        .unwrap();
+    assert_eq!(out, "Name: [alice     ]");
+    out.clear();
+    text_app
+        .create_slider("Volume", 0.0, 100.0, 30.0)
+        .paint(&mut out)
+        .unwrap();
+    assert_eq!(out, "Volume: |###-------| 30");
+    out.clear();
+    text_app
+        .create_menu("File", &["Open", "Save", "Quit"])
+        .paint(&mut out)
+        .unwrap();
+    assert_eq!(out, "File: Open | Save | Quit");
+
+    let mut out = String::new();
+    LoginForm::new(&MacFactory).paint(&mut out).unwrap();
+    print!("{}", out);
+    // output:
+    /*
+    mac os text field: User =
+    mac os checkbox: Remember me (off)
+    mac os button: Log in
+    */
+    // A form mixing families does not compile:
+    // let form: LoginForm<MacFactory> = LoginForm { submit: WinButton(..), .. };
+    let mut out = String::new();
+    LoginForm::new(&TextFactory).paint(&mut out).unwrap();
+    assert_eq!(out, "User: [          ]\n[ ] Remember me\n[ Log in ]\n");
+
+    let mut out = String::new();
+    let dialog = app
+        .new_gui_factory("win")
+        .unwrap()
+        .create_dialog("Error", "Disk full");
+    dialog.paint(&mut out).unwrap();
+    println!("{}", out); // output: windows os dialog: Error - Disk full
+
+    // Widgets keep their state and react to events directly...
+    let factory = app.new_gui_factory("win").unwrap();
+    let mut field = factory.create_text_field("Name", "al");
+    field.handle_event(&Event::Key(Key::Char('i')));
+    assert_eq!(field.text(), "ali");
+    let mut zoom = factory.create_slider("Zoom", 0.0, 10.0, 5.0);
+    zoom.handle_event(&Event::Key(Key::Left));
+    assert_eq!(zoom.value(), 4.0);
+    let mut cb = factory.create_checkbox("Dark mode", false);
+    cb.set_checked(true);
+    assert!(cb.is_checked());
+
+    // ...or through an event loop fed with scripted input.
+    let log = Rc::new(RefCell::new(Vec::new()));
+    let factory = app.new_gui_factory("text").unwrap();
+    let mut ui = EventLoop::new();
+    let name = ui.add(factory.create_text_field("Name", ""));
+    let mut remember = factory.create_checkbox("Remember me", false);
+    let toggled = log.clone();
+    remember.on_toggle(Box::new(move |checked| {
+        toggled.borrow_mut().push(format!("remember: {}", checked))
+    }));
+    assert!(!remember.is_checked());
+    let remember = ui.add(remember);
+    let volume = ui.add(factory.create_slider("Volume", 0.0, 100.0, 50.0));
+    let mut save = factory.create_button("Save");
+    let clicked = log.clone();
+    save.on_click(Box::new(move || {
+        clicked.borrow_mut().push("save".to_string())
+    }));
+    let save = ui.add(save);
+
+    ui.post(Input::Focus(name));
+    for c in "bobx".chars() {
+        ui.post(Input::Key(Key::Char(c)));
+    }
+    ui.post(Input::Key(Key::Backspace));
+    ui.post(Input::Click(remember));
+    ui.post(Input::Key(Key::Tab));
+    ui.run();
+    assert_eq!(ui.focused(), Some(volume));
+    ui.post(Input::Key(Key::Right));
+    ui.post(Input::Key(Key::Right));
+    ui.post(Input::Click(save));
+    ui.post(Input::Key(Key::Enter));
+    ui.run();
+    assert_eq!(ui.focused(), Some(save));
+    assert_eq!(*log.borrow(), vec!["remember: true", "save", "save"]);
+    let mut out = String::new();
+    ui.paint(&mut out).unwrap();
+    print!("{}", out);
+    // output:
+    /*
+    Name: [bob       ]
+    [x] Remember me
+    Volume: |#######---| 70
+    [ Save ]
+    */
+
+    // The same window laid out by two themes.
+    let settings = |factory: &dyn GUIFactory| {
+        Window::new(
+            factory,
+            "Settings",
+            Layout::padding(Layout::column(vec![
+                Layout::grid(
+                    2,
+                    vec![
+                        Layout::widget(factory.create_checkbox("Wifi", true)),
+                        Layout::widget(factory.create_checkbox("Bluetooth", false)),
+                        Layout::widget(factory.create_checkbox("Airplane mode", false)),
+                        Layout::widget(factory.create_checkbox("Hotspot", false)),
+                    ],
+                ),
+                Layout::row(vec![
+                    Layout::widget(factory.create_button("OK")),
+                    Layout::widget(factory.create_button("Cancel")),
+                ]),
+            ])),
+        )
+    };
+    let window = settings(app.new_gui_factory("text").unwrap().as_ref());
+    let size = window.preferred_size();
+    assert_eq!(
+        size,
+        Size {
+            width: 33,
+            height: 7
+        }
+    );
+    let mut out = String::new();
+    window.paint(size, &mut out).unwrap();
+    print!("{}", out);
+    // output:
+    /*
+    Settings (33x7)
+      1,1 8x1 [x] Wifi
+      19,1 13x1 [ ] Bluetooth
+      1,3 17x1 [ ] Airplane mode
+      19,3 11x1 [ ] Hotspot
+      1,5 6x1 [ OK ]
+      8,5 10x1 [ Cancel ]
+    */
+    let window = settings(app.new_gui_factory("win").unwrap().as_ref());
+    let mut out = String::new();
+    window
+        .paint(
+            Size {
+                width: 300,
+                ..window.preferred_size()
+            },
+            &mut out,
+        )
+        .unwrap();
+    print!("{}", out);
+    // Columns too wide for the window shrink in proportion.
+    // output:
+    /*
+    Settings (300x103)
+      11,11 143x23 windows os checkbox: Wifi (on)
+      160,11 128x23 windows os checkbox: Bluetooth (off)
+      11,40 143x23 windows os checkbox: Airplane mode (off)
+      160,40 128x23 windows os checkbox: Hotspot (off)
+      11,69 124x23 windows os button: OK
+      141,69 147x23 windows os button: Cancel
+    */
+
+    if let Err(e) = app.new_gui_factory("linux") {
+        println!("{}", e); // output: unknown theme `linux`, known themes: mac, text, win
+    }
+
+    app.set_fallback("text").unwrap();
+    let fallback_app = app.new_gui_factory("linux").unwrap();
+    out.clear();
+    fallback_app
+        .create_button("Cancel")
+        .paint(&mut out)
+        .unwrap();
+    println!("{}", out); // output: [ Cancel ]
+
+    // Themes defined in TOML files instead of Rust code.
+    let library = ThemeLibrary::load_dir(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/creational/abstract_factory/themes"
+    ))
+    .unwrap();
+    let names = app.register_themes(&library).unwrap();
+    assert_eq!(names, ["amber", "classic", "dark", "retro"]);
+    let amber = library.resolve("amber").unwrap();
+    assert_eq!(amber.button.color.as_deref(), Some("#ffbf00"));
+    assert_eq!(amber.dialog.color.as_deref(), Some("red"));
+    let mut out = String::new();
+    let factory = app.new_gui_factory("amber").unwrap();
+    factory.create_button("OK").paint(&mut out).unwrap();
+    assert_eq!(out, "\x1b[38;2;255;191;0m[OK]\x1b[0m");
+    for name in ["classic", "retro", "amber"] {
+        let factory = app.new_gui_factory(name).unwrap();
+        let mut out = String::new();
+        factory.create_button("OK").paint(&mut out).unwrap();
+        out.push(' ');
+        factory
+            .create_checkbox("Wifi", true)
+            .paint(&mut out)
+            .unwrap();
+        out.push(' ');
+        factory
+            .create_slider("Volume", 0.0, 100.0, 50.0)
+            .paint(&mut out)
+            .unwrap();
+        println!("{:<8}{}", name, out);
+    }
+    // output, with the buttons in black, green and amber:
+    /*
+    classic <OK> [x] Wifi Volume ====.... 50
+    retro   [OK] [#] Wifi Volume ====.... 50
+    amber   [OK] [#] Wifi Volume ****.... 50
+    */
+    assert_eq!(
+        app.new_gui_factory("retro").unwrap().metrics().char_width,
+        9
+    );
+
+    let mut library = ThemeLibrary::new();
+    library
+        .add(
+            "broken",
+            "extends = \"base\"\n[button]\ntemplate = \"{caption}\"\nborder = \"(\"\ncolor = \"teal\"\n",
+        )
+        .unwrap();
+    if let Err(e) = library.resolve("broken") {
+        println!("{}", e); // output: theme `broken` extends unknown theme `base`
+    }
+    library
+        .add(
+            "base",
+            &fs::read_to_string(concat!(
+                env!("CARGO_MANIFEST_DIR"),
+                "/creational/abstract_factory/themes/classic.toml"
+            ))
+            .unwrap(),
+        )
+        .unwrap();
+    if let Err(e) = library.resolve("broken") {
+        println!("{}", e);
+    }
+    // output:
+    /*
+    theme `broken` is invalid:
+      button.template: unknown placeholder `{caption}`, expected one of label
+      button.border: must be two characters, found `(`
+      button.color: `teal` is neither #rrggbb nor one of black, white, gray, red, green, yellow, blue, magenta, cyan
+    */
+    library.add("a", "extends = \"b\"").unwrap();
+    library.add("b", "extends = \"a\"").unwrap();
+    if let Err(e) = library.resolve("a") {
+        println!("{}", e); // output: themes extend each other: a -> b -> a
+    }
+    if let Err(e) = library.add("typo", "[button]\ntemplate = {label}\n") {
+        println!("{}", e); // output: theme `typo`, line 2: expected `.`, `=`
+    }
+
+    // Switching themes at runtime rebuilds the widgets but keeps their state.
+    let log = Rc::new(RefCell::new(Vec::new()));
+    let (toggles, clicks) = (log.clone(), log.clone());
+    let mut ui = Ui::new(
+        app.new_gui_factory("classic").unwrap(),
+        "Preferences",
+        View::padding(View::column(vec![
+            View::text_field("name", "Name", ""),
+            View::checkbox("dark", "Dark mode", false)
+                .on_toggle(move |on| toggles.borrow_mut().push(format!("dark {}", on))),
+            View::slider("volume", "Volume", 0.0, 100.0, 50.0),
+            View::row(vec![
+                View::button("ok", "OK").on_click(move || clicks.borrow_mut().push("ok".into())),
+                View::button("cancel", "Cancel"),
+            ]),
+        ])),
+    );
+    assert!(ui.set_focus("name"));
+    for c in "bob".chars() {
+        ui.handle_event("name", &Event::Key(Key::Char(c)));
+    }
+    ui.handle_event("dark", &Event::Click);
+    ui.handle_event("volume", &Event::Key(Key::Right));
+    assert!(!ui.handle_event("missing", &Event::Click));
+    let before = ui.state();
+    app.switch_theme(&mut ui, "dark").unwrap();
+    assert_eq!(ui.state(), before);
+    assert_eq!(before["name"], WidgetState::Text("bob".to_string()));
+    let mut out = String::new();
+    ui.paint(&mut out).unwrap();
+    print!("{}", out);
+    // output:
+    /*
+    Preferences (160x108)
+      8,8 80x20 Name > bob
+      8,32 104x20 [*] Dark mode
+      8,56 144x20 Volume =====... 60
+      8,80 32x20 (OK)
+      44,80 64x20 (Cancel)
+    */
+    app.switch_theme(&mut ui, "text").unwrap();
+    assert_eq!(ui.factory().metrics().char_width, 1);
+    let mut out = String::new();
+    ui.paint(&mut out).unwrap();
+    assert!(out.contains("Name: [bob       ]\n"));
+    assert!(out.contains("[x] Dark mode\n"));
+    // The callbacks and the focus carry over to the widgets of the new theme.
+    ui.handle_event("dark", &Event::Click);
+    ui.handle_event("ok", &Event::Click);
+    assert_eq!(*log.borrow(), ["dark true", "dark false", "ok"]);
+    assert_eq!(ui.focused(), Some("name"));
+
+    // Every registered theme paints the same gallery as its snapshot.
+    let snapshots = Snapshots::new(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/creational/abstract_factory/snapshots"
+    ));
+    let failures: Vec<String> = app
+        .themes()
+        .filter_map(|name| {
+            let factory = app.new_gui_factory(name).unwrap();
+            let gallery = snapshot::gallery(factory.as_ref());
+            snapshots.check(name, &gallery).err()
+        })
+        .map(|e| e.to_string())
+        .collect();
+    assert!(failures.is_empty(), "{}", failures.join("\n"));
+
+    // A golden file that differs only in its line endings still fails.
+    if !Snapshots::updating() {
+        let dir = env::temp_dir().join(format!("snapshots-{}", process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let gallery = snapshot::gallery(app.new_gui_factory("text").unwrap().as_ref());
+        fs::write(dir.join("text.txt"), gallery.replace('\n', "\r\n")).unwrap();
+        let error = Snapshots::new(&dir).check("text", &gallery).unwrap_err();
+        fs::remove_dir_all(&dir).unwrap();
+        println!("{}", error);
+        // output:
+        /*
+        snapshot `text` differs at line 10:
+          expected: <final newline, CRLF line endings>
+          actual:   <final newline, LF line endings>
+        */
+    }
 }
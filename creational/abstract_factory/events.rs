@@ -0,0 +1,125 @@
+//! Synthetic input events and a loop dispatching them to widgets, whichever
+//! `GUIFactory` created them.
+
+use std::collections::VecDeque;
+use std::fmt;
+
+use crate::Widget;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Key {
+    Char(char),
+    Backspace,
+    Enter,
+    Left,
+    Right,
+    Tab,
+}
+
+/// An event as seen by a single widget.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Event {
+    Click,
+    Key(Key),
+    Focus,
+    Blur,
+}
+
+/// Position of a widget in its `EventLoop`.
+pub type WidgetId = usize;
+
+/// Input posted to the loop. Clicks and focus changes name their target,
+/// keys go to the focused widget, and `Key::Tab` moves the focus on.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Input {
+    Click(WidgetId),
+    Key(Key),
+    Focus(WidgetId),
+}
+
+pub struct EventLoop {
+    widgets: Vec<Box<dyn Widget>>,
+    focus: Option<WidgetId>,
+    queue: VecDeque<Input>,
+}
+
+impl EventLoop {
+    pub fn new() -> EventLoop {
+        EventLoop {
+            widgets: Vec::new(),
+            focus: None,
+            queue: VecDeque::new(),
+        }
+    }
+
+    pub fn add(&mut self, widget: Box<dyn Widget>) -> WidgetId {
+        self.widgets.push(widget);
+        self.widgets.len() - 1
+    }
+
+    pub fn focused(&self) -> Option<WidgetId> {
+        self.focus
+    }
+
+    pub fn post(&mut self, input: Input) {
+        self.queue.push_back(input);
+    }
+
+    /// Dispatches every queued input and returns how many events the
+    /// widgets handled.
+    pub fn run(&mut self) -> usize {
+        let mut handled = 0;
+        while let Some(input) = self.queue.pop_front() {
+            handled += self.dispatch(input);
+        }
+        handled
+    }
+
+    fn dispatch(&mut self, input: Input) -> usize {
+        match input {
+            Input::Click(id) if id < self.widgets.len() => {
+                self.set_focus(id) + self.send(id, Event::Click)
+            }
+            Input::Focus(id) if id < self.widgets.len() => self.set_focus(id),
+            Input::Key(Key::Tab) if !self.widgets.is_empty() => {
+                let next = self.focus.map_or(0, |id| (id + 1) % self.widgets.len());
+                self.set_focus(next)
+            }
+            Input::Key(key) => match self.focus {
+                Some(id) => self.send(id, Event::Key(key)),
+                None => 0,
+            },
+            _ => 0,
+        }
+    }
+
+    fn set_focus(&mut self, id: WidgetId) -> usize {
+        if self.focus == Some(id) {
+            return 0;
+        }
+        let blurred = match self.focus.replace(id) {
+            Some(old) => self.send(old, Event::Blur),
+            None => 0,
+        };
+        blurred + self.send(id, Event::Focus)
+    }
+
+    fn send(&mut self, id: WidgetId, event: Event) -> usize {
+        self.widgets[id].handle_event(&event) as usize
+    }
+
+    /// Paints every widget on its own line.
+    pub fn paint(&self, out: &mut dyn fmt::Write) -> fmt::Result {
+        for widget in &self.widgets {
+            widget.paint(out)?;
+            writeln!(out)?;
+        }
+        Ok(())
+    }
+}
+
+impl Default for EventLoop {
+    fn default() -> EventLoop {
+        EventLoop::new()
+    }
+}
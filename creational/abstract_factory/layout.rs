@@ -0,0 +1,313 @@
+//! Container widgets and the layout pass placing their children. Sizes are
+//! in the units of the theme, e.g. pixels for desktop themes and character
+//! cells for the text theme, as described by its `Metrics`.
+
+use std::fmt;
+
+use serde::Deserialize;
+
+use crate::{GUIFactory, Widget};
+
+/// Measurements a theme contributes to layout.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Metrics {
+    /// Size of one character of painted text.
+    pub char_width: u32,
+    pub line_height: u32,
+    /// Gap between the children of a container.
+    pub spacing: u32,
+    /// Inset used by `Layout::padding`.
+    pub padding: u32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Size {
+    pub width: u32,
+    pub height: u32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rect {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// A tree of widgets inside rows, columns, grids and padding.
+pub enum Layout {
+    Widget(Box<dyn Widget>),
+    Row(Vec<Layout>),
+    Column(Vec<Layout>),
+    Grid {
+        columns: usize,
+        children: Vec<Layout>,
+    },
+    Padding(Box<Layout>),
+}
+
+impl Layout {
+    pub fn widget(widget: Box<dyn Widget>) -> Layout {
+        Layout::Widget(widget)
+    }
+    pub fn row(children: Vec<Layout>) -> Layout {
+        Layout::Row(children)
+    }
+    pub fn column(children: Vec<Layout>) -> Layout {
+        Layout::Column(children)
+    }
+    /// Children fill the grid row by row.
+    pub fn grid(columns: usize, children: Vec<Layout>) -> Layout {
+        Layout::Grid {
+            columns: columns.max(1),
+            children,
+        }
+    }
+    pub fn padding(child: Layout) -> Layout {
+        Layout::Padding(Box::new(child))
+    }
+
+    /// The widgets of the tree, depth first.
+    pub fn widgets(&self) -> Vec<&dyn Widget> {
+        match self {
+            Layout::Widget(widget) => vec![widget.as_ref()],
+            Layout::Row(children) | Layout::Column(children) | Layout::Grid { children, .. } => {
+                children.iter().flat_map(|child| child.widgets()).collect()
+            }
+            Layout::Padding(child) => child.widgets(),
+        }
+    }
+
+    pub fn widgets_mut(&mut self) -> Vec<&mut dyn Widget> {
+        match self {
+            Layout::Widget(widget) => vec![widget.as_mut()],
+            Layout::Row(children) | Layout::Column(children) | Layout::Grid { children, .. } => {
+                children
+                    .iter_mut()
+                    .flat_map(|child| child.widgets_mut())
+                    .collect()
+            }
+            Layout::Padding(child) => child.widgets_mut(),
+        }
+    }
+
+    pub fn preferred_size(&self, metrics: &Metrics) -> Size {
+        match self {
+            Layout::Widget(widget) => widget.preferred_size(metrics),
+            Layout::Row(children) => {
+                let sizes = sizes(children, metrics);
+                Size {
+                    width: sum(sizes.iter().map(|s| s.width), metrics.spacing),
+                    height: sizes.iter().map(|s| s.height).max().unwrap_or(0),
+                }
+            }
+            Layout::Column(children) => {
+                let sizes = sizes(children, metrics);
+                Size {
+                    width: sizes.iter().map(|s| s.width).max().unwrap_or(0),
+                    height: sum(sizes.iter().map(|s| s.height), metrics.spacing),
+                }
+            }
+            Layout::Grid { columns, children } => {
+                let (widths, heights) = grid_tracks(*columns, children, metrics);
+                Size {
+                    width: sum(widths.into_iter(), metrics.spacing),
+                    height: sum(heights.into_iter(), metrics.spacing),
+                }
+            }
+            Layout::Padding(child) => {
+                let size = child.preferred_size(metrics);
+                Size {
+                    width: size.width + 2 * metrics.padding,
+                    height: size.height + 2 * metrics.padding,
+                }
+            }
+        }
+    }
+
+    /// Places every widget inside `bounds`, depth first. Children keep their
+    /// preferred size when it fits and shrink proportionally when it does not.
+    pub fn arrange(&self, bounds: Rect, metrics: &Metrics) -> Vec<(&dyn Widget, Rect)> {
+        let mut placed = Vec::new();
+        self.arrange_into(bounds, metrics, &mut placed);
+        placed
+    }
+
+    fn arrange_into<'a>(
+        &'a self,
+        bounds: Rect,
+        metrics: &Metrics,
+        placed: &mut Vec<(&'a dyn Widget, Rect)>,
+    ) {
+        match self {
+            Layout::Widget(widget) => {
+                let size = widget.preferred_size(metrics);
+                placed.push((
+                    widget.as_ref(),
+                    Rect {
+                        width: size.width.min(bounds.width),
+                        height: size.height.min(bounds.height),
+                        ..bounds
+                    },
+                ));
+            }
+            Layout::Row(children) => {
+                let sizes = sizes(children, metrics);
+                let widths = fit(sizes.iter().map(|s| s.width), bounds.width, metrics.spacing);
+                let mut x = bounds.x;
+                for (child, width) in children.iter().zip(widths) {
+                    let cell = Rect { x, width, ..bounds };
+                    child.arrange_into(cell, metrics, placed);
+                    x += width + metrics.spacing;
+                }
+            }
+            Layout::Column(children) => {
+                let sizes = sizes(children, metrics);
+                let heights = fit(
+                    sizes.iter().map(|s| s.height),
+                    bounds.height,
+                    metrics.spacing,
+                );
+                let mut y = bounds.y;
+                for (child, height) in children.iter().zip(heights) {
+                    let cell = Rect {
+                        y,
+                        height,
+                        ..bounds
+                    };
+                    child.arrange_into(cell, metrics, placed);
+                    y += height + metrics.spacing;
+                }
+            }
+            Layout::Grid { columns, children } => {
+                let (widths, heights) = grid_tracks(*columns, children, metrics);
+                let widths = fit(widths.into_iter(), bounds.width, metrics.spacing);
+                let heights = fit(heights.into_iter(), bounds.height, metrics.spacing);
+                let offsets = |tracks: &[u32], start: u32| -> Vec<u32> {
+                    tracks
+                        .iter()
+                        .scan(start, |next, track| {
+                            let offset = *next;
+                            *next += track + metrics.spacing;
+                            Some(offset)
+                        })
+                        .collect()
+                };
+                let (xs, ys) = (offsets(&widths, bounds.x), offsets(&heights, bounds.y));
+                for (i, child) in children.iter().enumerate() {
+                    let (row, col) = (i / columns, i % columns);
+                    let cell = Rect {
+                        x: xs[col],
+                        y: ys[row],
+                        width: widths[col],
+                        height: heights[row],
+                    };
+                    child.arrange_into(cell, metrics, placed);
+                }
+            }
+            Layout::Padding(child) => {
+                let p = metrics.padding;
+                let inner = Rect {
+                    x: bounds.x + p,
+                    y: bounds.y + p,
+                    width: bounds.width.saturating_sub(2 * p),
+                    height: bounds.height.saturating_sub(2 * p),
+                };
+                child.arrange_into(inner, metrics, placed);
+            }
+        }
+    }
+}
+
+fn sizes(children: &[Layout], metrics: &Metrics) -> Vec<Size> {
+    children.iter().map(|c| c.preferred_size(metrics)).collect()
+}
+
+/// Total length of `tracks` laid out with `spacing` between them.
+fn sum(tracks: impl Iterator<Item = u32>, spacing: u32) -> u32 {
+    let (count, total) = tracks.fold((0u32, 0), |(n, total), t| (n + 1, total + t));
+    total + spacing * count.saturating_sub(1)
+}
+
+/// Shrinks `tracks` proportionally so they fit into `available`.
+fn fit(tracks: impl Iterator<Item = u32>, available: u32, spacing: u32) -> Vec<u32> {
+    let tracks: Vec<u32> = tracks.collect();
+    let gaps = spacing * (tracks.len() as u32).saturating_sub(1);
+    let room = available.saturating_sub(gaps) as u64;
+    let wanted: u64 = tracks.iter().map(|&t| t as u64).sum();
+    if wanted <= room {
+        return tracks;
+    }
+    tracks
+        .iter()
+        .map(|&t| (t as u64 * room / wanted) as u32)
+        .collect()
+}
+
+/// Column widths and row heights of a grid, from its largest children.
+fn grid_tracks(columns: usize, children: &[Layout], metrics: &Metrics) -> (Vec<u32>, Vec<u32>) {
+    let rows = children.len().div_ceil(columns);
+    let mut widths = vec![0; columns.min(children.len())];
+    let mut heights = vec![0; rows];
+    for (i, size) in sizes(children, metrics).iter().enumerate() {
+        widths[i % columns] = widths[i % columns].max(size.width);
+        heights[i / columns] = heights[i / columns].max(size.height);
+    }
+    (widths, heights)
+}
+
+/// A top-level layout sized and measured with the metrics of the factory
+/// that created its widgets.
+pub struct Window {
+    title: String,
+    metrics: Metrics,
+    root: Layout,
+}
+
+impl Window {
+    pub fn new(factory: &dyn GUIFactory, title: &str, root: Layout) -> Window {
+        Window {
+            title: title.to_string(),
+            metrics: factory.metrics(),
+            root,
+        }
+    }
+
+    pub fn preferred_size(&self) -> Size {
+        self.root.preferred_size(&self.metrics)
+    }
+
+    pub fn root(&self) -> &Layout {
+        &self.root
+    }
+
+    pub fn root_mut(&mut self) -> &mut Layout {
+        &mut self.root
+    }
+
+    pub fn arrange(&self, size: Size) -> Vec<(&dyn Widget, Rect)> {
+        let bounds = Rect {
+            x: 0,
+            y: 0,
+            width: size.width,
+            height: size.height,
+        };
+        self.root.arrange(bounds, &self.metrics)
+    }
+
+    /// Paints the window at `size`, one line per widget with its placement.
+    pub fn paint(&self, size: Size, out: &mut dyn fmt::Write) -> fmt::Result {
+        writeln!(out, "{} ({}x{})", self.title, size.width, size.height)?;
+        for (widget, rect) in self.arrange(size) {
+            write!(
+                out,
+                "  {},{} {}x{} ",
+                rect.x, rect.y, rect.width, rect.height
+            )?;
+            widget.paint(out)?;
+            writeln!(out)?;
+        }
+        Ok(())
+    }
+}
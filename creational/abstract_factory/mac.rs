@@ -0,0 +1,108 @@
+//! The macOS widget family.
+
+use std::fmt;
+
+use crate::layout::Metrics;
+use crate::{
+    ButtonModel, CheckboxModel, DialogModel, MenuModel, SliderModel, TextFieldModel, WidgetFamily,
+};
+
+pub struct MacFactory;
+
+impl WidgetFamily for MacFactory {
+    type Button = MacButton;
+    type Checkbox = MacCheckbox;
+    type TextField = MacTextField;
+    type Slider = MacSlider;
+    type Menu = MacMenu;
+    type Dialog = MacDialog;
+
+    fn button(&self, model: ButtonModel) -> MacButton {
+        MacButton(model)
+    }
+    fn checkbox(&self, model: CheckboxModel) -> MacCheckbox {
+        MacCheckbox(model)
+    }
+    fn text_field(&self, model: TextFieldModel) -> MacTextField {
+        MacTextField(model)
+    }
+    fn slider(&self, model: SliderModel) -> MacSlider {
+        MacSlider(model)
+    }
+    fn menu(&self, model: MenuModel) -> MacMenu {
+        MacMenu(model)
+    }
+    fn dialog(&self, model: DialogModel) -> MacDialog {
+        MacDialog(model)
+    }
+    fn metrics(&self) -> Metrics {
+        Metrics {
+            char_width: 8,
+            line_height: 22,
+            spacing: 8,
+            padding: 20,
+        }
+    }
+}
+
+pub struct MacButton(ButtonModel);
+impl MacButton {
+    fn paint(&self, out: &mut dyn fmt::Write) -> fmt::Result {
+        write!(out, "mac os button: {}", self.0.label)
+    }
+}
+
+pub struct MacCheckbox(CheckboxModel);
+impl MacCheckbox {
+    fn paint(&self, out: &mut dyn fmt::Write) -> fmt::Result {
+        let state = if self.0.checked { "on" } else { "off" };
+        write!(out, "mac os checkbox: {} ({})", self.0.label, state)
+    }
+}
+
+pub struct MacTextField(TextFieldModel);
+impl MacTextField {
+    fn paint(&self, out: &mut dyn fmt::Write) -> fmt::Result {
+        write!(out, "mac os text field: {} = {}", self.0.label, self.0.text)
+    }
+}
+
+pub struct MacSlider(SliderModel);
+impl MacSlider {
+    fn paint(&self, out: &mut dyn fmt::Write) -> fmt::Result {
+        let m = &self.0;
+        write!(
+            out,
+            "mac os slider: {} = {} ({}..{})",
+            m.label, m.value, m.min, m.max
+        )
+    }
+}
+
+pub struct MacMenu(MenuModel);
+impl MacMenu {
+    fn paint(&self, out: &mut dyn fmt::Write) -> fmt::Result {
+        write!(
+            out,
+            "mac os menu: {} [{}]",
+            self.0.title,
+            self.0.items.join(", ")
+        )
+    }
+}
+
+pub struct MacDialog(DialogModel);
+impl MacDialog {
+    fn paint(&self, out: &mut dyn fmt::Write) -> fmt::Result {
+        write!(out, "mac os dialog: {} - {}", self.0.title, self.0.message)
+    }
+}
+
+family_widgets!(
+    MacButton,
+    MacCheckbox,
+    MacTextField,
+    MacSlider,
+    MacMenu,
+    MacDialog
+);
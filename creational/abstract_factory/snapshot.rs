@@ -0,0 +1,182 @@
+//! Golden-file snapshots of painted widgets. A snapshot is compared with the
+//! file of the same name in the snapshot directory; running with
+//! `UPDATE_SNAPSHOTS=1` writes the files instead, to accept a new look.
+//!
+//! The examples have no `#[test]`s, so `cargo test` does not compare the
+//! snapshots. The check of every theme runs at the end of the example's
+//! `main`, which panics listing the snapshots that differ, so CI checks them
+//! with
+//!
+//! ```text
+//! cargo run --bin abstract_factory
+//! ```
+
+use std::env;
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+use crate::layout::{Layout, Window};
+use crate::GUIFactory;
+
+pub const UPDATE_VAR: &str = "UPDATE_SNAPSHOTS";
+
+#[derive(Debug)]
+pub enum SnapshotError {
+    Io(io::Error),
+    Missing(PathBuf),
+    Mismatch {
+        name: String,
+        line: usize,
+        expected: String,
+        actual: String,
+    },
+}
+
+impl fmt::Display for SnapshotError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SnapshotError::Io(e) => write!(f, "cannot access snapshot: {}", e),
+            SnapshotError::Missing(path) => write!(
+                f,
+                "no snapshot at {}, run with {}=1 to create it",
+                path.display(),
+                UPDATE_VAR
+            ),
+            SnapshotError::Mismatch {
+                name,
+                line,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "snapshot `{}` differs at line {}:\n  expected: {}\n  actual:   {}",
+                name, line, expected, actual
+            ),
+        }
+    }
+}
+
+impl Error for SnapshotError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            SnapshotError::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for SnapshotError {
+    fn from(e: io::Error) -> SnapshotError {
+        SnapshotError::Io(e)
+    }
+}
+
+pub struct Snapshots {
+    dir: PathBuf,
+    update: bool,
+}
+
+impl Snapshots {
+    /// Snapshots stored in `dir`, updated when `UPDATE_SNAPSHOTS` is set.
+    pub fn new<P: Into<PathBuf>>(dir: P) -> Snapshots {
+        Snapshots {
+            dir: dir.into(),
+            update: Self::updating(),
+        }
+    }
+
+    /// Whether `UPDATE_SNAPSHOTS` asks to write the snapshots instead of
+    /// comparing them. An empty value or `0` does not.
+    pub fn updating() -> bool {
+        env::var_os(UPDATE_VAR).is_some_and(|v| !v.is_empty() && v != "0")
+    }
+
+    /// Compares `actual` with the `name` snapshot, or stores it when updating.
+    pub fn check(&self, name: &str, actual: &str) -> Result<(), SnapshotError> {
+        let path = self.dir.join(format!("{}.txt", name));
+        if self.update {
+            fs::create_dir_all(&self.dir)?;
+            fs::write(&path, actual)?;
+            return Ok(());
+        }
+        let expected = match fs::read_to_string(&path) {
+            Ok(expected) => expected,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {
+                return Err(SnapshotError::Missing(path))
+            }
+            Err(e) => return Err(e.into()),
+        };
+        if expected == actual {
+            return Ok(());
+        }
+        let (expected_text, actual_text) = (expected.as_str(), actual);
+        let (mut expected, mut actual) = (expected.lines(), actual.lines());
+        let mut line = 1;
+        loop {
+            match (expected.next(), actual.next()) {
+                // Same lines, so only the line endings or the final newline
+                // differ.
+                (None, None) => {
+                    return Err(SnapshotError::Mismatch {
+                        name: name.to_string(),
+                        line,
+                        expected: Self::ending(expected_text),
+                        actual: Self::ending(actual_text),
+                    })
+                }
+                (e, a) if e == a => line += 1,
+                (e, a) => {
+                    return Err(SnapshotError::Mismatch {
+                        name: name.to_string(),
+                        line,
+                        expected: e.unwrap_or("<end of snapshot>").to_string(),
+                        actual: a.unwrap_or("<end of output>").to_string(),
+                    })
+                }
+            }
+        }
+    }
+
+    /// How `text` ends, to explain a mismatch that is not in any line.
+    fn ending(text: &str) -> String {
+        let newline = if text.ends_with('\n') {
+            "final newline"
+        } else {
+            "no final newline"
+        };
+        let endings = match (text.matches("\r\n").count(), text.matches('\n').count()) {
+            (0, _) => "LF",
+            (crlf, lf) if crlf == lf => "CRLF",
+            _ => "mixed",
+        };
+        format!("<{}, {} line endings>", newline, endings)
+    }
+}
+
+/// Paints one widget of every kind, in a window, with `factory`.
+pub fn gallery(factory: &dyn GUIFactory) -> String {
+    let root = Layout::padding(Layout::column(vec![
+        Layout::widget(factory.create_menu("File", &["Open", "Save", "Quit"])),
+        Layout::widget(factory.create_text_field("Name", "alice")),
+        Layout::grid(
+            2,
+            vec![
+                Layout::widget(factory.create_checkbox("Wifi", true)),
+                Layout::widget(factory.create_checkbox("Bluetooth", false)),
+            ],
+        ),
+        Layout::widget(factory.create_slider("Volume", 0.0, 10.0, 3.0)),
+        Layout::row(vec![
+            Layout::widget(factory.create_button("OK")),
+            Layout::widget(factory.create_button("Cancel")),
+        ]),
+        Layout::widget(factory.create_dialog("Error", "Disk full")),
+    ]));
+    let window = Window::new(factory, "Gallery", root);
+    let mut out = String::new();
+    window.paint(window.preferred_size(), &mut out).unwrap();
+    out
+}
@@ -0,0 +1,121 @@
+//! A widget family rendering plain text, for tests and headless servers.
+
+use std::fmt;
+
+use crate::layout::Metrics;
+use crate::{
+    ButtonModel, CheckboxModel, DialogModel, MenuModel, SliderModel, TextFieldModel, WidgetFamily,
+};
+
+/// Width of text fields and slider tracks, in characters.
+const WIDTH: usize = 10;
+
+pub struct TextFactory;
+
+impl WidgetFamily for TextFactory {
+    type Button = TextButton;
+    type Checkbox = TextCheckbox;
+    type TextField = TextTextField;
+    type Slider = TextSlider;
+    type Menu = TextMenu;
+    type Dialog = TextDialog;
+
+    fn button(&self, model: ButtonModel) -> TextButton {
+        TextButton(model)
+    }
+    fn checkbox(&self, model: CheckboxModel) -> TextCheckbox {
+        TextCheckbox(model)
+    }
+    fn text_field(&self, model: TextFieldModel) -> TextTextField {
+        TextTextField(model)
+    }
+    fn slider(&self, model: SliderModel) -> TextSlider {
+        TextSlider(model)
+    }
+    fn menu(&self, model: MenuModel) -> TextMenu {
+        TextMenu(model)
+    }
+    fn dialog(&self, model: DialogModel) -> TextDialog {
+        TextDialog(model)
+    }
+    fn metrics(&self) -> Metrics {
+        Metrics {
+            char_width: 1,
+            line_height: 1,
+            spacing: 1,
+            padding: 1,
+        }
+    }
+}
+
+pub struct TextButton(ButtonModel);
+impl TextButton {
+    fn paint(&self, out: &mut dyn fmt::Write) -> fmt::Result {
+        write!(out, "[ {} ]", self.0.label)
+    }
+}
+
+pub struct TextCheckbox(CheckboxModel);
+impl TextCheckbox {
+    fn paint(&self, out: &mut dyn fmt::Write) -> fmt::Result {
+        let mark = if self.0.checked { 'x' } else { ' ' };
+        write!(out, "[{}] {}", mark, self.0.label)
+    }
+}
+
+pub struct TextTextField(TextFieldModel);
+impl TextTextField {
+    fn paint(&self, out: &mut dyn fmt::Write) -> fmt::Result {
+        write!(
+            out,
+            "{}: [{:<width$}]",
+            self.0.label,
+            self.0.text,
+            width = WIDTH
+        )
+    }
+}
+
+pub struct TextSlider(SliderModel);
+impl TextSlider {
+    fn paint(&self, out: &mut dyn fmt::Write) -> fmt::Result {
+        let m = &self.0;
+        let ratio = if m.max > m.min {
+            (m.value - m.min) / (m.max - m.min)
+        } else {
+            0.0
+        };
+        let filled = (ratio * WIDTH as f64).round() as usize;
+        write!(
+            out,
+            "{}: |{}{}| {}",
+            m.label,
+            "#".repeat(filled),
+            "-".repeat(WIDTH - filled),
+            m.value
+        )
+    }
+}
+
+pub struct TextMenu(MenuModel);
+impl TextMenu {
+    fn paint(&self, out: &mut dyn fmt::Write) -> fmt::Result {
+        write!(out, "{}: {}", self.0.title, self.0.items.join(" | "))
+    }
+}
+
+pub struct TextDialog(DialogModel);
+impl TextDialog {
+    fn paint(&self, out: &mut dyn fmt::Write) -> fmt::Result {
+        write!(out, "(!) {}: {}", self.0.title, self.0.message)
+    }
+}
+
+family_widgets!(
+    TextButton,
+    TextCheckbox,
+    TextTextField,
+    TextSlider,
+    TextMenu,
+    TextDialog
+);
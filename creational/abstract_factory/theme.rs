@@ -0,0 +1,506 @@
+//! Widget families described by TOML theme files instead of Rust code. A theme
+//! gives the layout metrics and, for every kind of widget, how it looks:
+//!
+//! ```toml
+//! extends = "classic"
+//!
+//! [metrics]
+//! char_width = 9
+//!
+//! [button]
+//! template = "{label}"
+//! border = "[]"
+//! color = "green"
+//! ```
+//!
+//! `template` is painted with the `{placeholders}` of its widget filled in,
+//! `border` holds the two characters put around it and `color` is a color name
+//! or `#rrggbb`, painted as an ANSI terminal color. A theme extending another
+//! one only lists what it changes.
+
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::rc::Rc;
+
+use serde::Deserialize;
+use toml::{Table, Value};
+
+use crate::layout::Metrics;
+use crate::{
+    ButtonModel, CheckboxModel, DialogModel, MenuModel, SliderModel, TextFieldModel, WidgetFamily,
+};
+
+/// Color names with their ANSI foreground codes.
+const COLORS: &[(&str, u8)] = &[
+    ("black", 30),
+    ("white", 37),
+    ("gray", 90),
+    ("red", 31),
+    ("green", 32),
+    ("yellow", 33),
+    ("blue", 34),
+    ("magenta", 35),
+    ("cyan", 36),
+];
+
+#[derive(Debug)]
+pub enum ThemeError {
+    Io(io::Error),
+    Parse {
+        theme: String,
+        line: usize,
+        message: String,
+    },
+    UnknownTheme(String),
+    UnknownBase {
+        theme: String,
+        base: String,
+    },
+    /// Themes extending each other in a loop, in the order they were followed.
+    Cycle(Vec<String>),
+    Invalid {
+        theme: String,
+        problems: Vec<String>,
+    },
+}
+
+impl fmt::Display for ThemeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ThemeError::Io(e) => write!(f, "cannot read theme: {}", e),
+            ThemeError::Parse {
+                theme,
+                line,
+                message,
+            } => write!(f, "theme `{}`, line {}: {}", theme, line, message),
+            ThemeError::UnknownTheme(name) => write!(f, "unknown theme `{}`", name),
+            ThemeError::UnknownBase { theme, base } => {
+                write!(f, "theme `{}` extends unknown theme `{}`", theme, base)
+            }
+            ThemeError::Cycle(names) => {
+                write!(f, "themes extend each other: {}", names.join(" -> "))
+            }
+            ThemeError::Invalid { theme, problems } => {
+                write!(f, "theme `{}` is invalid:", theme)?;
+                for problem in problems {
+                    write!(f, "\n  {}", problem)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl Error for ThemeError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ThemeError::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for ThemeError {
+    fn from(e: io::Error) -> ThemeError {
+        ThemeError::Io(e)
+    }
+}
+
+const RESET: &str = "\x1b[0m";
+
+/// How one kind of widget is painted. Only some kinds use the glyphs.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Look {
+    pub template: String,
+    pub border: Option<String>,
+    pub color: Option<String>,
+    checked: Option<String>,
+    unchecked: Option<String>,
+    filled: Option<String>,
+    empty: Option<String>,
+    width: Option<usize>,
+    separator: Option<String>,
+}
+
+impl Look {
+    fn glyph<'a>(&'a self, glyph: &'a Option<String>, default: &'a str) -> &'a str {
+        glyph.as_deref().unwrap_or(default)
+    }
+
+    /// The ANSI escape selecting `color`, which `validate` accepted.
+    fn escape(color: &str) -> String {
+        match COLORS.iter().find(|(name, _)| *name == color) {
+            Some((_, code)) => format!("\x1b[{}m", code),
+            None => {
+                let channel = |i: usize| u8::from_str_radix(&color[i..i + 2], 16).unwrap_or(0);
+                format!("\x1b[38;2;{};{};{}m", channel(1), channel(3), channel(5))
+            }
+        }
+    }
+
+    /// Fills in the template and puts the border around it, in the color of
+    /// the look if it has one.
+    fn paint(&self, values: &[(&str, &str)], out: &mut dyn fmt::Write) -> fmt::Result {
+        if let Some(color) = &self.color {
+            out.write_str(&Self::escape(color))?;
+        }
+        let mut border = self.border.iter().flat_map(|b| b.chars());
+        if let Some(left) = border.next() {
+            out.write_char(left)?;
+        }
+        let mut rest = self.template.as_str();
+        while let Some(start) = rest.find('{') {
+            let end = start + rest[start..].find('}').unwrap_or(rest.len() - start - 1);
+            out.write_str(&rest[..start])?;
+            let name = &rest[start + 1..end];
+            let value = values.iter().find(|(key, _)| *key == name);
+            out.write_str(value.map_or("", |(_, value)| value))?;
+            rest = &rest[end + 1..];
+        }
+        out.write_str(rest)?;
+        if let Some(right) = border.next() {
+            out.write_char(right)?;
+        }
+        if self.color.is_some() {
+            out.write_str(RESET)?;
+        }
+        Ok(())
+    }
+
+    /// Lists what is wrong with the look of the `kind` widget.
+    fn validate(&self, kind: &str, placeholders: &[&str], problems: &mut Vec<String>) {
+        let mut rest = self.template.as_str();
+        while let Some(start) = rest.find('{') {
+            match rest[start..].find('}') {
+                Some(end) => {
+                    let name = &rest[start + 1..start + end];
+                    if !placeholders.contains(&name) {
+                        problems.push(format!(
+                            "{}.template: unknown placeholder `{{{}}}`, expected one of {}",
+                            kind,
+                            name,
+                            placeholders.join(", ")
+                        ));
+                    }
+                    rest = &rest[start + end + 1..];
+                }
+                None => {
+                    problems.push(format!("{}.template: unclosed `{{`", kind));
+                    break;
+                }
+            }
+        }
+        if let Some(border) = &self.border {
+            if border.chars().count() != 2 {
+                problems.push(format!(
+                    "{}.border: must be two characters, found `{}`",
+                    kind, border
+                ));
+            }
+        }
+        if let Some(color) = &self.color {
+            let hex = color.len() == 7
+                && color.starts_with('#')
+                && color[1..].chars().all(|c| c.is_ascii_hexdigit());
+            if !hex && !COLORS.iter().any(|(name, _)| name == color) {
+                let names: Vec<&str> = COLORS.iter().map(|(name, _)| *name).collect();
+                problems.push(format!(
+                    "{}.color: `{}` is neither #rrggbb nor one of {}",
+                    kind,
+                    color,
+                    names.join(", ")
+                ));
+            }
+        }
+        let glyphs = [
+            ("checked", &self.checked),
+            ("unchecked", &self.unchecked),
+            ("filled", &self.filled),
+            ("empty", &self.empty),
+        ];
+        for (name, glyph) in glyphs {
+            if let Some(glyph) = glyph {
+                if glyph.chars().count() != 1 {
+                    problems.push(format!(
+                        "{}.{}: must be a single character, found `{}`",
+                        kind, name, glyph
+                    ));
+                }
+            }
+        }
+    }
+}
+
+/// A fully resolved theme, with everything inherited from its bases.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Theme {
+    metrics: Metrics,
+    pub button: Look,
+    pub checkbox: Look,
+    pub text_field: Look,
+    pub slider: Look,
+    pub menu: Look,
+    pub dialog: Look,
+}
+
+impl Theme {
+    fn validate(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+        let looks: [(&str, &Look, &[&str]); 6] = [
+            ("button", &self.button, &["label"]),
+            ("checkbox", &self.checkbox, &["label", "mark"]),
+            ("text_field", &self.text_field, &["label", "text"]),
+            (
+                "slider",
+                &self.slider,
+                &["label", "value", "min", "max", "track"],
+            ),
+            ("menu", &self.menu, &["title", "items"]),
+            ("dialog", &self.dialog, &["title", "message"]),
+        ];
+        for (kind, look, placeholders) in looks {
+            look.validate(kind, placeholders, &mut problems);
+        }
+        let m = &self.metrics;
+        if m.char_width == 0 || m.line_height == 0 {
+            problems.push("metrics: char_width and line_height must be positive".to_string());
+        }
+        problems
+    }
+}
+
+/// Theme sources by name, resolved into `Theme`s on demand so that a theme
+/// can extend one added after it.
+pub struct ThemeLibrary {
+    sources: BTreeMap<String, Table>,
+}
+
+impl ThemeLibrary {
+    pub fn new() -> ThemeLibrary {
+        ThemeLibrary {
+            sources: BTreeMap::new(),
+        }
+    }
+
+    /// Adds every `*.toml` file in `dir`, named after the file.
+    pub fn load_dir<P: AsRef<Path>>(dir: P) -> Result<ThemeLibrary, ThemeError> {
+        let mut library = ThemeLibrary::new();
+        for entry in fs::read_dir(dir)? {
+            let path = entry?.path();
+            if path.extension().is_some_and(|ext| ext == "toml") {
+                let name = path.file_stem().unwrap().to_string_lossy().into_owned();
+                library.add(&name, &fs::read_to_string(&path)?)?;
+            }
+        }
+        Ok(library)
+    }
+
+    pub fn add(&mut self, name: &str, source: &str) -> Result<(), ThemeError> {
+        let table: Table = toml::from_str(source).map_err(|e| ThemeError::Parse {
+            theme: name.to_string(),
+            line: e
+                .span()
+                .map_or(1, |span| source[..span.start].matches('\n').count() + 1),
+            message: e.message().to_string(),
+        })?;
+        self.sources.insert(name.to_string(), table);
+        Ok(())
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.sources.keys().map(|name| name.as_str())
+    }
+
+    /// Merges `name` over the themes it extends and checks the result.
+    pub fn resolve(&self, name: &str) -> Result<Theme, ThemeError> {
+        let mut chain: Vec<&str> = Vec::new();
+        let mut next = Some(name);
+        while let Some(current) = next {
+            if chain.contains(&current) {
+                let mut names: Vec<String> = chain.iter().map(|n| n.to_string()).collect();
+                names.push(current.to_string());
+                return Err(ThemeError::Cycle(names));
+            }
+            let table = self
+                .sources
+                .get(current)
+                .ok_or_else(|| match chain.last() {
+                    Some(theme) => ThemeError::UnknownBase {
+                        theme: theme.to_string(),
+                        base: current.to_string(),
+                    },
+                    None => ThemeError::UnknownTheme(current.to_string()),
+                })?;
+            chain.push(current);
+            next = match table.get("extends") {
+                None => None,
+                Some(Value::String(base)) => Some(base),
+                Some(_) => {
+                    return Err(ThemeError::Invalid {
+                        theme: current.to_string(),
+                        problems: vec!["extends: must be the name of a theme".to_string()],
+                    })
+                }
+            };
+        }
+
+        let mut merged = Table::new();
+        for theme in chain.iter().rev() {
+            merge(&mut merged, &self.sources[*theme]);
+        }
+        merged.remove("extends");
+        let invalid = |problems| ThemeError::Invalid {
+            theme: name.to_string(),
+            problems,
+        };
+        let theme: Theme = Value::Table(merged)
+            .try_into()
+            .map_err(|e: toml::de::Error| invalid(vec![e.message().to_string()]))?;
+        let problems = theme.validate();
+        if problems.is_empty() {
+            Ok(theme)
+        } else {
+            Err(invalid(problems))
+        }
+    }
+}
+
+/// Overrides the keys of `base` with those of `theme`, merging nested tables.
+fn merge(base: &mut Table, theme: &Table) {
+    for (key, value) in theme {
+        match (base.get_mut(key), value) {
+            (Some(Value::Table(base)), Value::Table(table)) => merge(base, table),
+            _ => {
+                base.insert(key.clone(), value.clone());
+            }
+        }
+    }
+}
+
+pub struct ThemeFactory {
+    theme: Rc<Theme>,
+}
+
+impl ThemeFactory {
+    pub fn new(theme: Rc<Theme>) -> ThemeFactory {
+        ThemeFactory { theme }
+    }
+}
+
+impl WidgetFamily for ThemeFactory {
+    type Button = ThemedButton;
+    type Checkbox = ThemedCheckbox;
+    type TextField = ThemedTextField;
+    type Slider = ThemedSlider;
+    type Menu = ThemedMenu;
+    type Dialog = ThemedDialog;
+
+    fn button(&self, model: ButtonModel) -> ThemedButton {
+        ThemedButton(model, self.theme.clone())
+    }
+    fn checkbox(&self, model: CheckboxModel) -> ThemedCheckbox {
+        ThemedCheckbox(model, self.theme.clone())
+    }
+    fn text_field(&self, model: TextFieldModel) -> ThemedTextField {
+        ThemedTextField(model, self.theme.clone())
+    }
+    fn slider(&self, model: SliderModel) -> ThemedSlider {
+        ThemedSlider(model, self.theme.clone())
+    }
+    fn menu(&self, model: MenuModel) -> ThemedMenu {
+        ThemedMenu(model, self.theme.clone())
+    }
+    fn dialog(&self, model: DialogModel) -> ThemedDialog {
+        ThemedDialog(model, self.theme.clone())
+    }
+    fn metrics(&self) -> Metrics {
+        self.theme.metrics
+    }
+}
+
+pub struct ThemedButton(ButtonModel, Rc<Theme>);
+impl ThemedButton {
+    fn paint(&self, out: &mut dyn fmt::Write) -> fmt::Result {
+        self.1.button.paint(&[("label", &self.0.label)], out)
+    }
+}
+
+pub struct ThemedCheckbox(CheckboxModel, Rc<Theme>);
+impl ThemedCheckbox {
+    fn paint(&self, out: &mut dyn fmt::Write) -> fmt::Result {
+        let look = &self.1.checkbox;
+        let mark = if self.0.checked {
+            look.glyph(&look.checked, "x")
+        } else {
+            look.glyph(&look.unchecked, " ")
+        };
+        look.paint(&[("label", &self.0.label), ("mark", mark)], out)
+    }
+}
+
+pub struct ThemedTextField(TextFieldModel, Rc<Theme>);
+impl ThemedTextField {
+    fn paint(&self, out: &mut dyn fmt::Write) -> fmt::Result {
+        let values = [("label", self.0.label.as_str()), ("text", &self.0.text)];
+        self.1.text_field.paint(&values, out)
+    }
+}
+
+pub struct ThemedSlider(SliderModel, Rc<Theme>);
+impl ThemedSlider {
+    fn paint(&self, out: &mut dyn fmt::Write) -> fmt::Result {
+        let (m, look) = (&self.0, &self.1.slider);
+        let width = look.width.unwrap_or(10);
+        let ratio = if m.max > m.min {
+            (m.value - m.min) / (m.max - m.min)
+        } else {
+            0.0
+        };
+        let filled = (ratio * width as f64).round() as usize;
+        let track = look.glyph(&look.filled, "#").repeat(filled)
+            + &look.glyph(&look.empty, "-").repeat(width - filled);
+        let values = [
+            ("label", m.label.as_str()),
+            ("value", &m.value.to_string()),
+            ("min", &m.min.to_string()),
+            ("max", &m.max.to_string()),
+            ("track", &track),
+        ];
+        look.paint(&values, out)
+    }
+}
+
+pub struct ThemedMenu(MenuModel, Rc<Theme>);
+impl ThemedMenu {
+    fn paint(&self, out: &mut dyn fmt::Write) -> fmt::Result {
+        let look = &self.1.menu;
+        let items = self.0.items.join(look.glyph(&look.separator, ", "));
+        look.paint(&[("title", &self.0.title), ("items", &items)], out)
+    }
+}
+
+pub struct ThemedDialog(DialogModel, Rc<Theme>);
+impl ThemedDialog {
+    fn paint(&self, out: &mut dyn fmt::Write) -> fmt::Result {
+        let values = [
+            ("title", self.0.title.as_str()),
+            ("message", &self.0.message),
+        ];
+        self.1.dialog.paint(&values, out)
+    }
+}
+
+family_widgets!(
+    ThemedButton,
+    ThemedCheckbox,
+    ThemedTextField,
+    ThemedSlider,
+    ThemedMenu,
+    ThemedDialog
+);
@@ -0,0 +1,280 @@
+//! A declarative description of a window, so that it can be built again with
+//! another `GUIFactory`. Widgets are named by an id; when the theme changes
+//! the `Ui` reads their state and hands it to the widgets of the new family,
+//! along with the callbacks of the view and the focus.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::rc::Rc;
+
+use crate::layout::{Layout, Window};
+use crate::{Event, GUIFactory};
+
+/// What the user may have changed in a widget since it was built.
+#[derive(Debug, Clone, PartialEq)]
+pub enum WidgetState {
+    Checked(bool),
+    Text(String),
+    Value(f64),
+}
+
+pub enum View {
+    Button {
+        id: String,
+        label: String,
+        on_click: Vec<Rc<dyn Fn()>>,
+    },
+    Checkbox {
+        id: String,
+        label: String,
+        checked: bool,
+        on_toggle: Vec<Rc<dyn Fn(bool)>>,
+    },
+    TextField {
+        id: String,
+        label: String,
+        text: String,
+    },
+    Slider {
+        id: String,
+        label: String,
+        min: f64,
+        max: f64,
+        value: f64,
+    },
+    Row(Vec<View>),
+    Column(Vec<View>),
+    Padding(Box<View>),
+}
+
+impl View {
+    pub fn button(id: &str, label: &str) -> View {
+        View::Button {
+            id: id.to_string(),
+            label: label.to_string(),
+            on_click: Vec::new(),
+        }
+    }
+    pub fn checkbox(id: &str, label: &str, checked: bool) -> View {
+        View::Checkbox {
+            id: id.to_string(),
+            label: label.to_string(),
+            checked,
+            on_toggle: Vec::new(),
+        }
+    }
+    pub fn text_field(id: &str, label: &str, text: &str) -> View {
+        View::TextField {
+            id: id.to_string(),
+            label: label.to_string(),
+            text: text.to_string(),
+        }
+    }
+    pub fn slider(id: &str, label: &str, min: f64, max: f64, value: f64) -> View {
+        View::Slider {
+            id: id.to_string(),
+            label: label.to_string(),
+            min,
+            max,
+            value,
+        }
+    }
+    pub fn row(children: Vec<View>) -> View {
+        View::Row(children)
+    }
+    pub fn column(children: Vec<View>) -> View {
+        View::Column(children)
+    }
+    pub fn padding(child: View) -> View {
+        View::Padding(Box::new(child))
+    }
+
+    /// Calls `callback` when the button is clicked, whichever family the
+    /// view is built with.
+    ///
+    /// Panics if the view is not a button.
+    pub fn on_click<F: Fn() + 'static>(mut self, callback: F) -> View {
+        match &mut self {
+            View::Button { on_click, .. } => on_click.push(Rc::new(callback)),
+            _ => panic!("on_click needs a button view"),
+        }
+        self
+    }
+
+    /// Calls `callback` with the new state when the checkbox is toggled.
+    ///
+    /// Panics if the view is not a checkbox.
+    pub fn on_toggle<F: Fn(bool) + 'static>(mut self, callback: F) -> View {
+        match &mut self {
+            View::Checkbox { on_toggle, .. } => on_toggle.push(Rc::new(callback)),
+            _ => panic!("on_toggle needs a checkbox view"),
+        }
+        self
+    }
+
+    /// The widget ids, in the depth first order of the widgets of the layout.
+    fn ids(&self) -> Vec<&str> {
+        match self {
+            View::Button { id, .. }
+            | View::Checkbox { id, .. }
+            | View::TextField { id, .. }
+            | View::Slider { id, .. } => vec![id],
+            View::Row(children) | View::Column(children) => {
+                children.iter().flat_map(|child| child.ids()).collect()
+            }
+            View::Padding(child) => child.ids(),
+        }
+    }
+
+    /// Builds the widgets with `factory`, preferring the `state` saved under
+    /// their id to the initial one of the view.
+    fn build(&self, factory: &dyn GUIFactory, state: &BTreeMap<String, WidgetState>) -> Layout {
+        match self {
+            View::Button {
+                label, on_click, ..
+            } => {
+                let mut button = factory.create_button(label);
+                for callback in on_click {
+                    let callback = callback.clone();
+                    button.on_click(Box::new(move || callback()));
+                }
+                Layout::widget(button)
+            }
+            View::Checkbox {
+                id,
+                label,
+                checked,
+                on_toggle,
+            } => {
+                let checked = match state.get(id) {
+                    Some(WidgetState::Checked(checked)) => *checked,
+                    _ => *checked,
+                };
+                let mut checkbox = factory.create_checkbox(label, checked);
+                for callback in on_toggle {
+                    let callback = callback.clone();
+                    checkbox.on_toggle(Box::new(move |checked| callback(checked)));
+                }
+                Layout::widget(checkbox)
+            }
+            View::TextField { id, label, text } => {
+                let text = match state.get(id) {
+                    Some(WidgetState::Text(text)) => text,
+                    _ => text,
+                };
+                Layout::widget(factory.create_text_field(label, text))
+            }
+            View::Slider {
+                id,
+                label,
+                min,
+                max,
+                value,
+            } => {
+                let value = match state.get(id) {
+                    Some(WidgetState::Value(value)) => *value,
+                    _ => *value,
+                };
+                Layout::widget(factory.create_slider(label, *min, *max, value))
+            }
+            View::Row(children) => Layout::row(Self::build_all(children, factory, state)),
+            View::Column(children) => Layout::column(Self::build_all(children, factory, state)),
+            View::Padding(child) => Layout::padding(child.build(factory, state)),
+        }
+    }
+
+    fn build_all(
+        children: &[View],
+        factory: &dyn GUIFactory,
+        state: &BTreeMap<String, WidgetState>,
+    ) -> Vec<Layout> {
+        children
+            .iter()
+            .map(|child| child.build(factory, state))
+            .collect()
+    }
+}
+
+/// A window together with the view and factory it was built from.
+pub struct Ui {
+    title: String,
+    view: View,
+    factory: Box<dyn GUIFactory>,
+    window: Window,
+    focus: Option<String>,
+}
+
+impl Ui {
+    pub fn new(factory: Box<dyn GUIFactory>, title: &str, view: View) -> Ui {
+        let window = Window::new(
+            factory.as_ref(),
+            title,
+            view.build(factory.as_ref(), &BTreeMap::new()),
+        );
+        Ui {
+            title: title.to_string(),
+            view,
+            factory,
+            window,
+            focus: None,
+        }
+    }
+
+    /// The state of every widget that has one, by id.
+    pub fn state(&self) -> BTreeMap<String, WidgetState> {
+        let widgets = self.window.root().widgets();
+        self.view
+            .ids()
+            .into_iter()
+            .zip(widgets)
+            .filter_map(|(id, widget)| Some((id.to_string(), widget.state()?)))
+            .collect()
+    }
+
+    /// Sends `event` to the widget `id`, returning whether it reacted.
+    pub fn handle_event(&mut self, id: &str, event: &Event) -> bool {
+        let index = self.view.ids().iter().position(|other| *other == id);
+        let mut widgets = self.window.root_mut().widgets_mut();
+        match index {
+            Some(index) => widgets[index].handle_event(event),
+            None => false,
+        }
+    }
+
+    pub fn focused(&self) -> Option<&str> {
+        self.focus.as_deref()
+    }
+
+    /// Moves the focus to the widget `id`, returning whether there is one.
+    pub fn set_focus(&mut self, id: &str) -> bool {
+        if !self.view.ids().contains(&id) {
+            return false;
+        }
+        if let Some(old) = self.focus.take() {
+            self.handle_event(&old, &Event::Blur);
+        }
+        self.handle_event(id, &Event::Focus);
+        self.focus = Some(id.to_string());
+        true
+    }
+
+    /// Rebuilds every widget with `factory`, keeping their state, their
+    /// callbacks and the focus.
+    pub fn set_factory(&mut self, factory: Box<dyn GUIFactory>) {
+        let state = self.state();
+        let root = self.view.build(factory.as_ref(), &state);
+        self.window = Window::new(factory.as_ref(), &self.title, root);
+        self.factory = factory;
+        if let Some(id) = self.focus.clone() {
+            self.handle_event(&id, &Event::Focus);
+        }
+    }
+
+    pub fn factory(&self) -> &dyn GUIFactory {
+        self.factory.as_ref()
+    }
+
+    pub fn paint(&self, out: &mut dyn fmt::Write) -> fmt::Result {
+        self.window.paint(self.window.preferred_size(), out)
+    }
+}
@@ -0,0 +1,116 @@
+//! The Windows widget family.
+
+use std::fmt;
+
+use crate::layout::Metrics;
+use crate::{
+    ButtonModel, CheckboxModel, DialogModel, MenuModel, SliderModel, TextFieldModel, WidgetFamily,
+};
+
+pub struct WinFactory;
+
+impl WidgetFamily for WinFactory {
+    type Button = WinButton;
+    type Checkbox = WinCheckbox;
+    type TextField = WinTextField;
+    type Slider = WinSlider;
+    type Menu = WinMenu;
+    type Dialog = WinDialog;
+
+    fn button(&self, model: ButtonModel) -> WinButton {
+        WinButton(model)
+    }
+    fn checkbox(&self, model: CheckboxModel) -> WinCheckbox {
+        WinCheckbox(model)
+    }
+    fn text_field(&self, model: TextFieldModel) -> WinTextField {
+        WinTextField(model)
+    }
+    fn slider(&self, model: SliderModel) -> WinSlider {
+        WinSlider(model)
+    }
+    fn menu(&self, model: MenuModel) -> WinMenu {
+        WinMenu(model)
+    }
+    fn dialog(&self, model: DialogModel) -> WinDialog {
+        WinDialog(model)
+    }
+    fn metrics(&self) -> Metrics {
+        Metrics {
+            char_width: 7,
+            line_height: 23,
+            spacing: 6,
+            padding: 11,
+        }
+    }
+}
+
+pub struct WinButton(ButtonModel);
+impl WinButton {
+    fn paint(&self, out: &mut dyn fmt::Write) -> fmt::Result {
+        write!(out, "windows os button: {}", self.0.label)
+    }
+}
+
+pub struct WinCheckbox(CheckboxModel);
+impl WinCheckbox {
+    fn paint(&self, out: &mut dyn fmt::Write) -> fmt::Result {
+        let state = if self.0.checked { "on" } else { "off" };
+        write!(out, "windows os checkbox: {} ({})", self.0.label, state)
+    }
+}
+
+pub struct WinTextField(TextFieldModel);
+impl WinTextField {
+    fn paint(&self, out: &mut dyn fmt::Write) -> fmt::Result {
+        write!(
+            out,
+            "windows os text field: {} = {}",
+            self.0.label, self.0.text
+        )
+    }
+}
+
+pub struct WinSlider(SliderModel);
+impl WinSlider {
+    fn paint(&self, out: &mut dyn fmt::Write) -> fmt::Result {
+        let m = &self.0;
+        write!(
+            out,
+            "windows os slider: {} = {} ({}..{})",
+            m.label, m.value, m.min, m.max
+        )
+    }
+}
+
+pub struct WinMenu(MenuModel);
+impl WinMenu {
+    fn paint(&self, out: &mut dyn fmt::Write) -> fmt::Result {
+        write!(
+            out,
+            "windows os menu: {} [{}]",
+            self.0.title,
+            self.0.items.join(", ")
+        )
+    }
+}
+
+pub struct WinDialog(DialogModel);
+impl WinDialog {
+    fn paint(&self, out: &mut dyn fmt::Write) -> fmt::Result {
+        write!(
+            out,
+            "windows os dialog: {} - {}",
+            self.0.title, self.0.message
+        )
+    }
+}
+
+family_widgets!(
+    WinButton,
+    WinCheckbox,
+    WinTextField,
+    WinSlider,
+    WinMenu,
+    WinDialog
+);
@@ -1,21 +1,65 @@
 //! Builder is a creational design pattern, which allows constructing complex objects step by step.
 
-#[derive(Clone)]
+#[path = "builder/diff.rs"]
+mod diff;
+#[path = "builder/edit.rs"]
+mod edit;
+#[path = "builder/fluent.rs"]
+mod fluent;
+#[path = "builder/parallel.rs"]
+mod parallel;
+#[path = "builder/recipe.rs"]
+mod recipe;
+#[path = "builder/render.rs"]
+mod render;
+#[path = "builder/rules.rs"]
+mod rules;
+#[cfg(feature = "serialize")]
+#[path = "builder/serialize.rs"]
+mod serialize;
+#[path = "builder/typestate.rs"]
+mod typestate;
+
+use std::collections::BTreeSet;
+use std::fmt;
+use std::io;
+use std::panic;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+use builder_derive::Builder;
+use diff::Change;
+use fluent::Fluent;
+use parallel::ParallelBuilder;
+use recipe::{Recipe, Step};
+use render::{Banner, MarkdownTable, PlainList, Render};
+use rules::{BuildError, Rules, Violation};
+use typestate::TypestateBuilder;
+
+/// Every part of the product is named and remembers which kind it is.
+#[derive(Clone, Debug, PartialEq)]
 struct Product {
-    parts: Vec<String>,
+    parts: Vec<(Part, String)>,
 }
 
 impl Product {
     fn new() -> Product {
         Product { parts: Vec::new() }
     }
+    fn add(&mut self, part: Part, name: &str) {
+        self.parts.push((part, name.to_string()));
+    }
+    fn names(&self) -> Vec<&str> {
+        self.parts.iter().map(|(_, name)| name.as_str()).collect()
+    }
+    fn render(&self, renderer: &dyn Render, out: &mut dyn io::Write) -> io::Result<()> {
+        renderer.render(self, out)
+    }
     fn list_parts(&self) {
-        let parts_list = String::from(" parts ");
-        println!("{0}{1}{0}", "*".repeat(10), parts_list);
-        for v in &self.parts {
-            println!("{}", v);
-        }
-        println!("{0}{1}{0}", "*".repeat(10), "*".repeat(parts_list.len()));
+        self.render(&Banner, &mut io::stdout()).unwrap();
     }
 }
 
@@ -27,7 +71,68 @@ trait Builder {
     fn produce_part_a(&mut self);
     fn produce_part_b(&mut self);
     fn produce_part_c(&mut self);
-    fn get_product(&mut self) -> Product;
+    /// The product built so far.
+    fn product(&self) -> &Product;
+    /// Hands over the product built so far and starts a new one.
+    fn reset(&mut self) -> Product;
+    fn rules(&self) -> &Rules;
+
+    /// Checks the product against the rules of the builder and hands it over
+    /// if it follows all of them. Otherwise the builder keeps it.
+    fn get_product(&mut self) -> Result<Product, BuildError> {
+        self.rules().validate(self.product())?;
+        Ok(self.reset())
+    }
+}
+
+/**
+ * The parts a Builder knows how to produce, so that building steps can be
+ * described as data.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serialize",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "lowercase")
+)]
+enum Part {
+    A,
+    B,
+    C,
+}
+
+impl fmt::Display for Part {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Part::A => "a",
+            Part::B => "b",
+            Part::C => "c",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl FromStr for Part {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Part, String> {
+        match name {
+            "a" => Ok(Part::A),
+            "b" => Ok(Part::B),
+            "c" => Ok(Part::C),
+            _ => Err(format!("unknown part `{}`, expected a, b or c", name)),
+        }
+    }
+}
+
+impl Part {
+    fn produce(self, builder: &mut dyn Builder) {
+        match self {
+            Part::A => builder.produce_part_a(),
+            Part::B => builder.produce_part_b(),
+            Part::C => builder.produce_part_c(),
+        }
+    }
 }
 
 /**
@@ -35,65 +140,90 @@ trait Builder {
  * specific implementations of the building steps. Your program may have several
  * variations of Builders, implemented differently.
  */
+#[derive(Clone)]
 struct ContreteBuilder1 {
     product: Product,
+    rules: Rules,
 }
 
 impl ContreteBuilder1 {
     fn new() -> ContreteBuilder1 {
+        ContreteBuilder1::with_rules(Rules::new())
+    }
+
+    fn with_rules(rules: Rules) -> ContreteBuilder1 {
         ContreteBuilder1 {
             product: Product::new(),
+            rules,
         }
     }
 }
 
 impl Builder for ContreteBuilder1 {
     fn produce_part_a(&mut self) {
-        self.product.parts.push("part a1".to_string());
+        self.product.add(Part::A, "part a1");
     }
     fn produce_part_b(&mut self) {
-        self.product.parts.push("part b1".to_string());
+        self.product.add(Part::B, "part b1");
     }
     fn produce_part_c(&mut self) {
-        self.product.parts.push("part c1".to_string());
+        self.product.add(Part::C, "part c1");
+    }
+    fn product(&self) -> &Product {
+        &self.product
     }
-    fn get_product(&mut self) -> Product {
+    fn reset(&mut self) -> Product {
         let p = self.product.clone();
         self.product = Product::new();
         p
     }
+    fn rules(&self) -> &Rules {
+        &self.rules
+    }
 }
 
+#[derive(Clone)]
 struct ContreteBuilder2 {
     product: Product,
+    rules: Rules,
 }
 
 impl ContreteBuilder2 {
     fn new() -> ContreteBuilder2 {
+        ContreteBuilder2::with_rules(Rules::new())
+    }
+
+    fn with_rules(rules: Rules) -> ContreteBuilder2 {
         ContreteBuilder2 {
             product: Product::new(),
+            rules,
         }
     }
 }
 
 impl Builder for ContreteBuilder2 {
     fn produce_part_a(&mut self) {
-        self.product.parts.push("part a ~~~~ 2".to_string());
+        self.product.add(Part::A, "part a ~~~~ 2");
     }
     fn produce_part_b(&mut self) {
-        self.product.parts.push("part b ~~~~ 2".to_string());
+        self.product.add(Part::B, "part b ~~~~ 2");
     }
     fn produce_part_c(&mut self) {
-        self.product.parts.push("part c ~~~~ 2".to_string());
+        self.product.add(Part::C, "part c ~~~~ 2");
     }
-    fn get_product(&mut self) -> Product {
+    fn product(&self) -> &Product {
+        &self.product
+    }
+    fn reset(&mut self) -> Product {
         let p = Product {
             parts: self.product.parts.clone(),
-            ..self.product
         };
         self.product = Product::new();
         p
     }
+    fn rules(&self) -> &Rules {
+        &self.rules
+    }
 }
 
 /**
@@ -104,25 +234,72 @@ impl Builder for ContreteBuilder2 {
  */
 struct Director {
     builder: Box<dyn Builder>,
+    flags: BTreeSet<String>,
 }
 
 impl Director {
-    fn new(builder: Box<Builder>) -> Director {
-        Director { builder: builder }
+    fn new(builder: Box<dyn Builder>) -> Director {
+        Director {
+            builder,
+            flags: BTreeSet::new(),
+        }
     }
 
     fn construct(&mut self) {
-        self.builder.produce_part_a();
-        self.builder.produce_part_b();
-        self.builder.produce_part_c();
+        self.construct_with(&Recipe::new(
+            "standard",
+            vec![Part::A.into(), Part::B.into(), Part::C.into()],
+        ));
     }
+
+    /// Enables the conditional steps of recipes that depend on `flag`.
+    fn enable(&mut self, flag: &str) {
+        self.flags.insert(flag.to_string());
+    }
+
+    fn construct_with(&mut self, recipe: &Recipe) {
+        self.run(&recipe.steps);
+    }
+
+    fn run(&mut self, steps: &[Step]) {
+        for step in steps {
+            match step {
+                Step::Part(part) => part.produce(self.builder.as_mut()),
+                Step::Repeat { repeat, steps } => {
+                    for _ in 0..*repeat {
+                        self.run(steps);
+                    }
+                }
+                Step::When { when, steps } => {
+                    if self.flags.contains(when) {
+                        self.run(steps);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/**
+ * Builders like the ones above can also be derived. Fields are required
+ * unless they are an `Option` or have a default.
+ */
+#[derive(Builder, Debug, PartialEq)]
+struct ServerConfig {
+    #[builder(into)]
+    host: String,
+    #[builder(default = 8080)]
+    port: u16,
+    #[builder(default)]
+    workers: usize,
+    certificate: Option<String>,
 }
 
 fn main() {
     let builder1 = Box::new(ContreteBuilder1::new());
     let mut direct = Director::new(builder1);
     direct.construct();
-    let product = direct.builder.get_product();
+    let product = direct.builder.get_product().unwrap();
     product.list_parts();
     // output:
     /*
@@ -136,7 +313,7 @@ fn main() {
     let build2 = Box::new(ContreteBuilder2::new());
     let mut direct = Director::new(build2);
     direct.construct();
-    let product = direct.builder.get_product();
+    let product = direct.builder.get_product().unwrap();
     product.list_parts();
     // output:
     /*
@@ -146,4 +323,341 @@ fn main() {
     part c ~~~~ 2
     ***************************
     */
+
+    // Required parts are checked by the compiler, optional ones can go anywhere.
+    let product = TypestateBuilder::new()
+        .part_c("sunroof")
+        .part_b("wheels")
+        .part_a("engine")
+        .part_c("radio")
+        .build();
+    product.list_parts();
+    // output:
+    /*
+    ********** parts **********
+    engine
+    wheels
+    sunroof
+    radio
+    ***************************
+    */
+    // Leaving out a required part does not compile, which no test checks:
+    // TypestateBuilder::new().part_a("engine").build();
+    // Nor does setting one twice:
+    // TypestateBuilder::new().part_a("engine").part_a("engine");
+
+    let mut builder = ServerConfig::builder();
+    builder.port(443).certificate("server.pem".to_string());
+    if let Err(e) = builder.build() {
+        println!("{}", e); // output: `host` must be set
+    }
+    let config = builder.host("example.com").build().unwrap();
+    assert_eq!(
+        config,
+        ServerConfig {
+            host: "example.com".to_string(),
+            port: 443,
+            workers: 0,
+            certificate: Some("server.pem".to_string()),
+        }
+    );
+    let config = ServerConfig::builder().host("localhost").workers(4).build();
+    assert_eq!(config.map(|c| (c.port, c.certificate)), Ok((8080, None)));
+    assert_eq!(
+        ServerConfig::builder().build(),
+        Err(ServerConfigBuilderError::UninitializedField("host"))
+    );
+
+    // One director, many product variants.
+    let recipes = Recipe::load_file(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/creational/builder/recipes.toml"
+    ))
+    .unwrap();
+    let mut director = Director::new(Box::new(ContreteBuilder1::new()));
+    for recipe in &recipes {
+        director.construct_with(recipe);
+        let product = director.builder.get_product().unwrap();
+        println!("{}: {}", recipe.name, product.names().join(", "));
+    }
+    // output:
+    /*
+    minimal: part a1
+    full: part a1, part b1, part c1, part c1
+    debug: part a1, part b1, part c1
+    */
+    director.enable("trace");
+    director.construct_with(&recipes[2]);
+    assert_eq!(director.builder.get_product().unwrap().parts.len(), 6);
+
+    let recipe = Recipe::new(
+        "twins",
+        vec![Step::repeat(2, vec![Part::A.into(), Part::B.into()])],
+    );
+    let mut director = Director::new(Box::new(ContreteBuilder2::new()));
+    director.construct_with(&recipe);
+    assert_eq!(
+        director.builder.get_product().unwrap().names(),
+        [
+            "part a ~~~~ 2",
+            "part b ~~~~ 2",
+            "part a ~~~~ 2",
+            "part b ~~~~ 2"
+        ]
+    );
+    let source = "[[recipe]]\nname = \"broken\"\nsteps = [\"a\", \"d\"]\n";
+    if let Err(e) = Recipe::load(source) {
+        println!("{}", e); // output: line 3: unknown part `d`, expected a, b or c
+    }
+
+    // Builders check their rules before handing the product over, and
+    // report every rule the product breaks.
+    let rules = Rules::new()
+        .require(Part::A)
+        .require(Part::B)
+        .unique(Part::C)
+        .before(Part::A, Part::C)
+        .check("at most 3 parts", |product| product.parts.len() <= 3);
+    let mut director = Director::new(Box::new(ContreteBuilder1::with_rules(rules)));
+    director.construct_with(&Recipe::new(
+        "sloppy",
+        vec![
+            Part::C.into(),
+            Part::A.into(),
+            Part::C.into(),
+            Part::C.into(),
+        ],
+    ));
+    let e = director.builder.get_product().unwrap_err();
+    println!("{}", e);
+    // output:
+    /*
+    invalid product:
+      part b is required
+      part c appears 3 times, at most once allowed
+      part a must come before part c
+      at most 3 parts
+    */
+    assert_eq!(e.violations[0], Violation::Missing(Part::B));
+    // The builder keeps the rejected product, so it can still be completed.
+    director.builder.produce_part_b();
+    assert_eq!(
+        director.builder.get_product().unwrap_err().violations.len(),
+        3
+    );
+    director.builder.reset();
+    director.construct();
+    assert_eq!(
+        director.builder.get_product().unwrap().names(),
+        ["part a1", "part b1", "part c1"]
+    );
+
+    // The same product rendered in other formats, into any io::Write.
+    let product = TypestateBuilder::new()
+        .part_a("engine")
+        .part_b("wheels")
+        .part_c("radio | tape")
+        .build();
+    let mut out = Vec::new();
+    product.render(&PlainList, &mut out).unwrap();
+    assert_eq!(
+        String::from_utf8(out).unwrap(),
+        "- engine\n- wheels\n- radio | tape\n"
+    );
+    let mut out = Vec::new();
+    product.render(&MarkdownTable, &mut out).unwrap();
+    print!("{}", String::from_utf8(out).unwrap());
+    // output:
+    /*
+    | # | Part | Name |
+    |---|------|------|
+    | 1 | a | engine |
+    | 2 | b | wheels |
+    | 3 | c | radio \| tape |
+    */
+
+    // With `--features serialize`, products round-trip through JSON and TOML.
+    #[cfg(feature = "serialize")]
+    {
+        let json = product.to_json().unwrap();
+        println!("{}", json);
+        // output: {"parts":[{"part":"a","name":"engine"},{"part":"b","name":"wheels"},{"part":"c","name":"radio | tape"}]}
+        assert_eq!(Product::from_json(&json).unwrap(), product);
+        let toml = product.to_toml().unwrap();
+        assert_eq!(Product::from_toml(&toml).unwrap(), product);
+        let mut out = Vec::new();
+        product.render(&render::Json, &mut out).unwrap();
+        assert_eq!(
+            Product::from_json(&String::from_utf8(out).unwrap()).unwrap(),
+            product
+        );
+        if let Err(e) = Product::from_json(r#"{"parts":[{"part":"d","name":"x"}]}"#) {
+            // output: unknown variant `d`, expected one of `a`, `b`, `c` at line 1 column 21
+            println!("{}", e);
+        }
+    }
+
+    // A fluent builder can branch "what if" variants off a shared base.
+    let rules = Rules::new().require(Part::A).unique(Part::B);
+    let base = Fluent::new(ContreteBuilder1::with_rules(rules))
+        .part_a()
+        .part_b();
+    let with_extras = base.snapshot().part_c().part_c();
+    let doubled = base.snapshot().part_b();
+    assert_eq!(
+        with_extras.build().unwrap().names(),
+        ["part a1", "part b1", "part c1", "part c1"]
+    );
+    if let Err(e) = doubled.snapshot().build() {
+        println!("{}", e);
+    }
+    // output:
+    /*
+    invalid product:
+      part b appears 2 times, at most once allowed
+    */
+    let fixed = doubled.undo();
+    assert_eq!(fixed.steps(), [Part::A, Part::B]);
+    assert_eq!(fixed.build(), base.build());
+    let product = Fluent::new(ContreteBuilder2::new())
+        .step(Part::C)
+        .part_a()
+        .build()
+        .unwrap();
+    assert_eq!(product.names(), ["part c ~~~~ 2", "part a ~~~~ 2"]);
+
+    // Expensive parts are produced on worker threads, in a fixed order.
+    let active = Arc::new(AtomicUsize::new(0));
+    let busiest = Arc::new(AtomicUsize::new(0));
+    let slow = |name: &'static str| {
+        let (active, busiest) = (active.clone(), busiest.clone());
+        move || {
+            let now = active.fetch_add(1, Ordering::SeqCst) + 1;
+            busiest.fetch_max(now, Ordering::SeqCst);
+            thread::sleep(Duration::from_millis(50));
+            active.fetch_sub(1, Ordering::SeqCst);
+            Ok(name.to_string())
+        }
+    };
+    let builder = ParallelBuilder::new(4)
+        .independent(Part::A, slow("engine"))
+        .independent(Part::C, slow("seat"))
+        .sequential(Part::B, slow("paint"));
+    let mut director = Director::new(Box::new(builder));
+    director.construct_with(&Recipe::new(
+        "car",
+        vec![
+            Part::A.into(),
+            Step::repeat(3, vec![Part::C.into()]),
+            Part::B.into(),
+        ],
+    ));
+    let product = director.builder.get_product().unwrap();
+    assert_eq!(product.names(), ["engine", "seat", "seat", "seat", "paint"]);
+    assert!(busiest.load(Ordering::SeqCst) > 1);
+
+    let engines = Arc::new(AtomicUsize::new(0));
+    let counter = engines.clone();
+    let builder = ParallelBuilder::new(2)
+        .independent(Part::A, move || {
+            counter.fetch_add(1, Ordering::SeqCst);
+            Ok("engine".to_string())
+        })
+        .independent(Part::B, || panic!("paint jammed"))
+        .with_rules(Rules::new().require(Part::C));
+    let mut director = Director::new(Box::new(builder));
+    director.construct();
+    // Keep the report of the caught panic off stderr.
+    let hook = panic::take_hook();
+    panic::set_hook(Box::new(|_| {}));
+    if let Err(e) = director.builder.get_product() {
+        println!("{}", e);
+    }
+    // output:
+    /*
+    invalid product:
+      part b failed: panicked: paint jammed
+      part c failed: no producer registered
+      part c is required
+    */
+
+    // The engine is kept, and trying again only retries the failed steps.
+    assert_eq!(director.builder.product().names(), ["engine"]);
+    assert!(director.builder.get_product().is_err());
+    assert_eq!(engines.load(Ordering::SeqCst), 1);
+    panic::set_hook(hook);
+
+    // A product can be edited through a builder again, and compared with
+    // the original to review the changes.
+    let mut director = Director::new(Box::new(ContreteBuilder1::new()));
+    director.construct_with(&Recipe::new(
+        "base",
+        vec![
+            Part::A.into(),
+            Part::B.into(),
+            Part::C.into(),
+            Part::C.into(),
+        ],
+    ));
+    let before = director.builder.get_product().unwrap();
+    let mut builder = before.to_builder().with_rules(Rules::new().unique(Part::A));
+    builder
+        .remove("part b1")
+        .add(Part::B, "part b ~~~~ 2")
+        .move_to("part a1", 3)
+        .rename("part c1", "part c1 (chrome)");
+    builder.produce_part_c();
+    let after = builder.get_product().unwrap();
+    assert_eq!(
+        after.names(),
+        [
+            "part c1 (chrome)",
+            "part c1",
+            "part b ~~~~ 2",
+            "part a1",
+            "part c"
+        ]
+    );
+    let diff = before.diff(&after);
+    println!("{}", diff);
+    // output:
+    /*
+    - [1] b: part b1
+    - [2] c: part c1
+    + [0] c: part c1 (chrome)
+    + [2] b: part b ~~~~ 2
+    + [4] c: part c
+    ~ [0 -> 3] a: part a1
+    */
+    assert_eq!(
+        diff.changes.last(),
+        Some(&Change::Moved {
+            from: 0,
+            to: 3,
+            part: Part::A,
+            name: "part a1".to_string()
+        })
+    );
+    assert!(before
+        .diff(&before.to_builder().get_product().unwrap())
+        .is_empty());
+    println!("{}", after.diff(&after)); // output: no changes
+
+    // A typo in an edit is reported instead of silently doing nothing.
+    let mut builder = after.to_builder();
+    builder
+        .remove("part b2")
+        .rename("part a1", "part a1 (steel)");
+    if let Err(e) = builder.get_product() {
+        println!("{}", e);
+    }
+    // output:
+    /*
+    invalid product:
+      cannot remove `part b2`: no such part
+    */
+
+    // The typo was reported, and the edits that worked are kept.
+    let product = builder.get_product().unwrap();
+    assert_eq!(product.names()[3], "part a1 (steel)");
 }
@@ -0,0 +1,217 @@
+//! `#[derive(Builder)]` writes the builder of a struct with named fields, in
+//! the shape of the hand-written builders of the builder example:
+//!
+//! ```ignore
+//! #[derive(Builder)]
+//! struct Server {
+//!     #[builder(into)]
+//!     host: String,
+//!     #[builder(default = 8080)]
+//!     port: u16,
+//!     #[builder(default)]
+//!     workers: usize,
+//!     certificate: Option<String>,
+//! }
+//!
+//! let server = Server::builder().host("localhost").workers(4).build()?;
+//! ```
+//!
+//! `ServerBuilder` gets a setter per field taking `&mut self`, so it can be
+//! chained or driven step by step, and `build(&self)` clones the values set
+//! so far into a `Server`. With `#[builder(into)]` a setter accepts anything
+//! convertible into the field type, e.g. a `&str` for a `String`. A field
+//! left unset is `None` if it is an `Option`, its default if it has a
+//! `#[builder(default)]` attribute, and otherwise makes `build` return
+//! `ServerBuilderError::UninitializedField`.
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{format_ident, quote};
+use syn::{
+    parse_macro_input, Data, DeriveInput, Error, Expr, Fields, GenericArgument, PathArguments, Type,
+};
+
+#[proc_macro_derive(Builder, attributes(builder))]
+pub fn derive_builder(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+/// What `build` does with a field that was never set.
+enum Unset {
+    Fail,
+    None,
+    Default,
+    Value(Expr),
+}
+
+fn expand(input: DeriveInput) -> syn::Result<TokenStream2> {
+    let name = &input.ident;
+    let vis = &input.vis;
+    let builder = format_ident!("{}Builder", name);
+    let error = format_ident!("{}BuilderError", name);
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => {
+                return Err(Error::new_spanned(
+                    name,
+                    "Builder can only be derived for structs with named fields",
+                ))
+            }
+        },
+        _ => {
+            return Err(Error::new_spanned(
+                name,
+                "Builder can only be derived for structs",
+            ))
+        }
+    };
+
+    let mut slots = Vec::new();
+    let mut setters = Vec::new();
+    let mut values = Vec::new();
+    for field in fields {
+        let ident = field.ident.as_ref().unwrap();
+        let ty = &field.ty;
+        let mut unset = match option_inner(ty) {
+            Some(_) => Unset::None,
+            None => Unset::Fail,
+        };
+        let mut into = false;
+        for attr in field.attrs.iter().filter(|a| a.path().is_ident("builder")) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("into") {
+                    into = true;
+                } else if meta.path.is_ident("default") {
+                    unset = if meta.input.is_empty() || meta.input.peek(syn::Token![,]) {
+                        Unset::Default
+                    } else {
+                        Unset::Value(meta.value()?.parse()?)
+                    };
+                } else {
+                    return Err(meta.error("expected `into`, `default` or `default = <expr>`"));
+                }
+                Ok(())
+            })?;
+        }
+
+        // Setters of optional fields take the value inside the `Option`.
+        let (slot_ty, wrap) = match (&unset, option_inner(ty)) {
+            (Unset::None, Some(inner)) => (inner, quote!(::std::option::Option::Some)),
+            _ => (ty, quote!()),
+        };
+        slots.push(quote! { #ident: ::std::option::Option<#slot_ty> });
+        setters.push(if into {
+            quote! {
+                #vis fn #ident(&mut self, value: impl ::std::convert::Into<#slot_ty>) -> &mut Self {
+                    self.#ident = ::std::option::Option::Some(value.into());
+                    self
+                }
+            }
+        } else {
+            quote! {
+                #vis fn #ident(&mut self, value: #slot_ty) -> &mut Self {
+                    self.#ident = ::std::option::Option::Some(value);
+                    self
+                }
+            }
+        });
+        let field_name = ident.to_string();
+        let value = match unset {
+            Unset::Fail => quote! {
+                match &self.#ident {
+                    ::std::option::Option::Some(value) => ::std::clone::Clone::clone(value),
+                    ::std::option::Option::None => {
+                        return ::std::result::Result::Err(#error::UninitializedField(#field_name))
+                    }
+                }
+            },
+            Unset::None => quote! { ::std::clone::Clone::clone(&self.#ident) },
+            Unset::Default => quote! {
+                ::std::option::Option::map_or_else(
+                    self.#ident.as_ref(),
+                    ::std::default::Default::default,
+                    |value| #wrap(::std::clone::Clone::clone(value)),
+                )
+            },
+            Unset::Value(expr) => quote! {
+                ::std::option::Option::map_or_else(
+                    self.#ident.as_ref(),
+                    || #expr,
+                    |value| #wrap(::std::clone::Clone::clone(value)),
+                )
+            },
+        };
+        values.push(quote! { #ident: #value });
+    }
+    let idents = fields.iter().map(|field| &field.ident);
+
+    let builder_doc = format!("Builds a [`{}`] one field at a time.", name);
+    let error_doc = format!("Why a [`{}`] could not be built.", builder);
+    Ok(quote! {
+        #[doc = #builder_doc]
+        #vis struct #builder #impl_generics #where_clause {
+            #(#slots,)*
+        }
+
+        #[doc = #error_doc]
+        #[derive(Debug, Clone, PartialEq, Eq)]
+        #vis enum #error {
+            /// A required field was never set.
+            UninitializedField(&'static str),
+        }
+
+        impl ::std::fmt::Display for #error {
+            fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+                match self {
+                    #error::UninitializedField(field) => write!(f, "`{}` must be set", field),
+                }
+            }
+        }
+
+        impl ::std::error::Error for #error {}
+
+        impl #impl_generics #name #ty_generics #where_clause {
+            #vis fn builder() -> #builder #ty_generics {
+                #builder::new()
+            }
+        }
+
+        impl #impl_generics #builder #ty_generics #where_clause {
+            #vis fn new() -> Self {
+                #builder {
+                    #(#idents: ::std::option::Option::None,)*
+                }
+            }
+
+            #(#setters)*
+
+            #vis fn build(&self) -> ::std::result::Result<#name #ty_generics, #error> {
+                ::std::result::Result::Ok(#name {
+                    #(#values,)*
+                })
+            }
+        }
+    })
+}
+
+/// The `T` of a field declared as `Option<T>`.
+fn option_inner(ty: &Type) -> Option<&Type> {
+    let Type::Path(path) = ty else { return None };
+    let segment = path.path.segments.last()?;
+    if segment.ident != "Option" {
+        return None;
+    }
+    match &segment.arguments {
+        PathArguments::AngleBracketed(args) if args.args.len() == 1 => match &args.args[0] {
+            GenericArgument::Type(inner) => Some(inner),
+            _ => None,
+        },
+        _ => None,
+    }
+}
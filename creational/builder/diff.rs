@@ -0,0 +1,140 @@
+//! What changed between two products. Parts are the same when both their kind
+//! and name are. The longest run of parts common to both products, in order,
+//! counts as unchanged; the other parts found in both were moved, and the
+//! rest were removed or added.
+
+use std::fmt;
+
+use crate::{Part, Product};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Change {
+    Removed {
+        index: usize,
+        part: Part,
+        name: String,
+    },
+    Added {
+        index: usize,
+        part: Part,
+        name: String,
+    },
+    Moved {
+        from: usize,
+        to: usize,
+        part: Part,
+        name: String,
+    },
+}
+
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Change::Removed { index, part, name } => write!(f, "- [{}] {}: {}", index, part, name),
+            Change::Added { index, part, name } => write!(f, "+ [{}] {}: {}", index, part, name),
+            Change::Moved {
+                from,
+                to,
+                part,
+                name,
+            } => write!(f, "~ [{} -> {}] {}: {}", from, to, part, name),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diff {
+    /// Removed parts first, then added, then moved ones, each by position.
+    pub changes: Vec<Change>,
+}
+
+impl Diff {
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+}
+
+impl fmt::Display for Diff {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.is_empty() {
+            return write!(f, "no changes");
+        }
+        for (i, change) in self.changes.iter().enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+            write!(f, "{}", change)?;
+        }
+        Ok(())
+    }
+}
+
+impl Product {
+    /// The changes turning `self` into `other`.
+    pub fn diff(&self, other: &Product) -> Diff {
+        let (old, new) = (&self.parts, &other.parts);
+        let (in_old, in_new) = common(old, new);
+
+        let mut unmatched_new: Vec<usize> = (0..new.len()).filter(|&j| !in_new[j]).collect();
+        let mut removed = Vec::new();
+        let mut moved = Vec::new();
+        for i in (0..old.len()).filter(|&i| !in_old[i]) {
+            match unmatched_new.iter().position(|&j| new[j] == old[i]) {
+                Some(k) => moved.push((i, unmatched_new.remove(k))),
+                None => removed.push(i),
+            }
+        }
+
+        let mut changes: Vec<Change> = removed
+            .into_iter()
+            .map(|index| Change::Removed {
+                index,
+                part: old[index].0,
+                name: old[index].1.clone(),
+            })
+            .collect();
+        changes.extend(unmatched_new.into_iter().map(|index| Change::Added {
+            index,
+            part: new[index].0,
+            name: new[index].1.clone(),
+        }));
+        moved.sort_by_key(|&(_, to)| to);
+        changes.extend(moved.into_iter().map(|(from, to)| Change::Moved {
+            from,
+            to,
+            part: new[to].0,
+            name: new[to].1.clone(),
+        }));
+        Diff { changes }
+    }
+}
+
+/// Marks the parts of the longest common subsequence of `old` and `new`.
+fn common<T: PartialEq>(old: &[T], new: &[T]) -> (Vec<bool>, Vec<bool>) {
+    // lengths[i][j] is the LCS length of old[i..] and new[j..].
+    let mut lengths = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lengths[i][j] = if old[i] == new[j] {
+                lengths[i + 1][j + 1] + 1
+            } else {
+                lengths[i + 1][j].max(lengths[i][j + 1])
+            };
+        }
+    }
+    let (mut in_old, mut in_new) = (vec![false; old.len()], vec![false; new.len()]);
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() && j < new.len() {
+        if old[i] == new[j] {
+            in_old[i] = true;
+            in_new[j] = true;
+            i += 1;
+            j += 1;
+        } else if lengths[i + 1][j] >= lengths[i][j + 1] {
+            i += 1;
+        } else {
+            j += 1;
+        }
+    }
+    (in_old, in_new)
+}
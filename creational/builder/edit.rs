@@ -0,0 +1,114 @@
+//! Turning a finished `Product` back into a builder to change it.
+
+use crate::rules::{BuildError, Rules, Violation};
+use crate::{Builder, Part, Product};
+
+/// A builder starting from an existing product. The building steps append
+/// parts with a generic name, the other methods edit the parts by name. An
+/// edit naming a part the product does not have makes the next
+/// `get_product` fail, and the builder keeps the product to go on editing.
+pub struct ProductBuilder {
+    product: Product,
+    rules: Rules,
+    unknown: Vec<Violation>,
+}
+
+impl Product {
+    pub fn to_builder(&self) -> ProductBuilder {
+        ProductBuilder {
+            product: self.clone(),
+            rules: Rules::new(),
+            unknown: Vec::new(),
+        }
+    }
+}
+
+impl ProductBuilder {
+    pub fn with_rules(mut self, rules: Rules) -> ProductBuilder {
+        self.rules = rules;
+        self
+    }
+
+    pub fn add(&mut self, part: Part, name: &str) -> &mut Self {
+        self.product.add(part, name);
+        self
+    }
+
+    /// The position of the first part called `name`, recording the `edit`
+    /// as failed if there is none.
+    fn position(&mut self, edit: &'static str, name: &str) -> Option<usize> {
+        let position = self
+            .product
+            .parts
+            .iter()
+            .position(|(_, other)| other == name);
+        if position.is_none() {
+            self.unknown.push(Violation::NoSuchPart {
+                edit,
+                name: name.to_string(),
+            });
+        }
+        position
+    }
+
+    /// Removes the first part called `name`.
+    pub fn remove(&mut self, name: &str) -> &mut Self {
+        if let Some(i) = self.position("remove", name) {
+            self.product.parts.remove(i);
+        }
+        self
+    }
+
+    pub fn rename(&mut self, name: &str, new_name: &str) -> &mut Self {
+        if let Some(i) = self.position("rename", name) {
+            self.product.parts[i].1 = new_name.to_string();
+        }
+        self
+    }
+
+    /// Moves the first part called `name` to `index`, or to the end.
+    pub fn move_to(&mut self, name: &str, index: usize) -> &mut Self {
+        if let Some(i) = self.position("move", name) {
+            let part = self.product.parts.remove(i);
+            let index = index.min(self.product.parts.len());
+            self.product.parts.insert(index, part);
+        }
+        self
+    }
+}
+
+impl Builder for ProductBuilder {
+    fn produce_part_a(&mut self) {
+        self.product.add(Part::A, "part a");
+    }
+    fn produce_part_b(&mut self) {
+        self.product.add(Part::B, "part b");
+    }
+    fn produce_part_c(&mut self) {
+        self.product.add(Part::C, "part c");
+    }
+    fn product(&self) -> &Product {
+        &self.product
+    }
+    fn reset(&mut self) -> Product {
+        self.unknown.clear();
+        std::mem::replace(&mut self.product, Product::new())
+    }
+    fn rules(&self) -> &Rules {
+        &self.rules
+    }
+
+    /// Reports the edits of unknown parts made since the last call, along
+    /// with the broken rules.
+    fn get_product(&mut self) -> Result<Product, BuildError> {
+        let mut violations = std::mem::take(&mut self.unknown);
+        if let Err(e) = self.rules.validate(&self.product) {
+            violations.extend(e.violations);
+        }
+        if violations.is_empty() {
+            Ok(self.reset())
+        } else {
+            Err(BuildError { violations })
+        }
+    }
+}
@@ -0,0 +1,69 @@
+//! A consuming, chainable front for any cloneable `Builder`:
+//!
+//! ```ignore
+//! let product = Fluent::new(ContreteBuilder1::new()).part_a().part_b().build()?;
+//! ```
+//!
+//! `Fluent` records the steps and only replays them on the builder in
+//! `build`, which consumes it. Until then it can be cloned at any point,
+//! builder included, to branch into variants, and the last step can be
+//! undone.
+
+use crate::rules::BuildError;
+use crate::{Builder, Part, Product};
+
+#[derive(Clone)]
+pub struct Fluent<B: Builder + Clone> {
+    base: B,
+    steps: Vec<Part>,
+}
+
+impl<B: Builder + Clone> Fluent<B> {
+    pub fn new(builder: B) -> Fluent<B> {
+        Fluent {
+            base: builder,
+            steps: Vec::new(),
+        }
+    }
+
+    pub fn step(mut self, part: Part) -> Fluent<B> {
+        self.steps.push(part);
+        self
+    }
+
+    pub fn part_a(self) -> Fluent<B> {
+        self.step(Part::A)
+    }
+
+    pub fn part_b(self) -> Fluent<B> {
+        self.step(Part::B)
+    }
+
+    pub fn part_c(self) -> Fluent<B> {
+        self.step(Part::C)
+    }
+
+    /// Forgets the last step, if any.
+    pub fn undo(mut self) -> Fluent<B> {
+        self.steps.pop();
+        self
+    }
+
+    /// A copy to branch a variant from, leaving this one untouched.
+    pub fn snapshot(&self) -> Fluent<B> {
+        self.clone()
+    }
+
+    pub fn steps(&self) -> &[Part] {
+        &self.steps
+    }
+
+    /// Runs the steps on the builder and validates the result.
+    pub fn build(self) -> Result<Product, BuildError> {
+        let mut builder = self.base;
+        for part in &self.steps {
+            part.produce(&mut builder);
+        }
+        builder.get_product()
+    }
+}
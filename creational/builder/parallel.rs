@@ -0,0 +1,201 @@
+//! A builder producing its parts on worker threads. Each part has a producer
+//! function, declared either independent, so that consecutive independent
+//! steps run at the same time, or sequential, so that it runs alone once the
+//! steps before it are done. Parts end up in the order of the steps whatever
+//! order the workers finish in, and every failed step is reported, including
+//! a producer that panics. The parts that were produced are kept, so building
+//! again only retries the failed steps.
+//!
+//! There is no pool kept between builds: every batch runs on scoped worker
+//! threads started for it, at most `threads` of them.
+
+use std::collections::HashMap;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
+
+use crate::rules::{BuildError, Rules, Violation};
+use crate::{Builder, Part, Product};
+
+type Producer = Box<dyn Fn() -> Result<String, String> + Send + Sync>;
+
+struct Registered {
+    produce: Producer,
+    independent: bool,
+}
+
+pub struct ParallelBuilder {
+    threads: usize,
+    producers: HashMap<Part, Registered>,
+    /// Every step since the last product, with the name of its part once
+    /// it was produced.
+    steps: Vec<(Part, Option<String>)>,
+    /// The parts produced so far, in the order of their steps.
+    product: Product,
+    rules: Rules,
+}
+
+impl ParallelBuilder {
+    pub fn new(threads: usize) -> ParallelBuilder {
+        ParallelBuilder {
+            threads: threads.max(1),
+            producers: HashMap::new(),
+            steps: Vec::new(),
+            product: Product::new(),
+            rules: Rules::new(),
+        }
+    }
+
+    pub fn independent<F>(self, part: Part, produce: F) -> ParallelBuilder
+    where
+        F: Fn() -> Result<String, String> + Send + Sync + 'static,
+    {
+        self.register(part, Box::new(produce), true)
+    }
+
+    pub fn sequential<F>(self, part: Part, produce: F) -> ParallelBuilder
+    where
+        F: Fn() -> Result<String, String> + Send + Sync + 'static,
+    {
+        self.register(part, Box::new(produce), false)
+    }
+
+    fn register(mut self, part: Part, produce: Producer, independent: bool) -> ParallelBuilder {
+        self.producers.insert(
+            part,
+            Registered {
+                produce,
+                independent,
+            },
+        );
+        self
+    }
+
+    pub fn with_rules(mut self, rules: Rules) -> ParallelBuilder {
+        self.rules = rules;
+        self
+    }
+
+    /// Produces the steps that are not done yet, batch by batch, and
+    /// returns the failures. The failed steps stay to be retried.
+    fn run_pending(&mut self) -> Vec<Violation> {
+        let pending: Vec<usize> = (0..self.steps.len())
+            .filter(|&i| self.steps[i].1.is_none())
+            .collect();
+        let mut failures = Vec::new();
+        let mut start = 0;
+        while start < pending.len() {
+            let independent = |i: &usize| {
+                let part = self.steps[*i].0;
+                self.producers.get(&part).is_none_or(|p| p.independent)
+            };
+            let len = if independent(&pending[start]) {
+                pending[start..]
+                    .iter()
+                    .take_while(|i| independent(i))
+                    .count()
+            } else {
+                1
+            };
+            let batch = &pending[start..start + len];
+            let parts: Vec<Part> = batch.iter().map(|&i| self.steps[i].0).collect();
+            for (&i, result) in batch.iter().zip(self.run_batch(&parts)) {
+                match result {
+                    Ok(name) => self.steps[i].1 = Some(name),
+                    Err(message) => failures.push(Violation::Failed {
+                        part: self.steps[i].0,
+                        message,
+                    }),
+                }
+            }
+            start += len;
+        }
+        self.product = Product::new();
+        for (part, name) in &self.steps {
+            if let Some(name) = name {
+                self.product.add(*part, name);
+            }
+        }
+        failures
+    }
+
+    /// Runs `batch` on up to `threads` workers, keeping the results in order.
+    fn run_batch(&self, batch: &[Part]) -> Vec<Result<String, String>> {
+        let results: Vec<Mutex<Option<Result<String, String>>>> =
+            batch.iter().map(|_| Mutex::new(None)).collect();
+        let next = AtomicUsize::new(0);
+        let producers = &self.producers;
+        thread::scope(|scope| {
+            for _ in 0..self.threads.min(batch.len()) {
+                scope.spawn(|| loop {
+                    let i = next.fetch_add(1, Ordering::SeqCst);
+                    let Some(part) = batch.get(i) else { break };
+                    let result = match producers.get(part) {
+                        Some(producer) => produce(producer),
+                        None => Err("no producer registered".to_string()),
+                    };
+                    *results[i].lock().unwrap() = Some(result);
+                });
+            }
+        });
+        results
+            .into_iter()
+            .map(|result| result.into_inner().unwrap().unwrap())
+            .collect()
+    }
+}
+
+/// Runs `producer`, turning a panic into a failure of its step.
+fn produce(producer: &Registered) -> Result<String, String> {
+    match panic::catch_unwind(AssertUnwindSafe(|| (producer.produce)())) {
+        Ok(result) => result,
+        Err(payload) => {
+            let message = match payload.downcast::<String>() {
+                Ok(message) => *message,
+                Err(payload) => match payload.downcast::<&str>() {
+                    Ok(message) => message.to_string(),
+                    Err(_) => "unknown panic".to_string(),
+                },
+            };
+            Err(format!("panicked: {}", message))
+        }
+    }
+}
+
+impl Builder for ParallelBuilder {
+    fn produce_part_a(&mut self) {
+        self.steps.push((Part::A, None));
+    }
+    fn produce_part_b(&mut self) {
+        self.steps.push((Part::B, None));
+    }
+    fn produce_part_c(&mut self) {
+        self.steps.push((Part::C, None));
+    }
+    /// The parts produced so far, without the steps that are not done yet.
+    fn product(&self) -> &Product {
+        &self.product
+    }
+    fn reset(&mut self) -> Product {
+        self.steps.clear();
+        std::mem::replace(&mut self.product, Product::new())
+    }
+    fn rules(&self) -> &Rules {
+        &self.rules
+    }
+
+    /// Produces the steps that are not done yet, then reports the failed
+    /// ones along with the broken rules.
+    fn get_product(&mut self) -> Result<Product, BuildError> {
+        let mut violations = self.run_pending();
+        if let Err(e) = self.rules.validate(&self.product) {
+            violations.extend(e.violations);
+        }
+        if violations.is_empty() {
+            Ok(self.reset())
+        } else {
+            Err(BuildError { violations })
+        }
+    }
+}
@@ -0,0 +1,152 @@
+//! Recipes are named sequences of building steps for a `Director`, written in
+//! code or loaded from TOML:
+//!
+//! ```toml
+//! [[recipe]]
+//! name = "full"
+//! steps = ["a", "b", { repeat = 2, steps = ["c"] }, { when = "debug", steps = ["c"] }]
+//! ```
+//!
+//! A step is a part, a list of steps repeated a number of times, or a list of
+//! steps only run when the director has the flag named by `when` enabled.
+
+use std::convert::TryFrom;
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use serde::Deserialize;
+use toml::Value;
+
+use crate::Part;
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(try_from = "Value")]
+pub enum Step {
+    Part(Part),
+    Repeat { repeat: usize, steps: Vec<Step> },
+    When { when: String, steps: Vec<Step> },
+}
+
+impl Step {
+    pub fn repeat(times: usize, steps: Vec<Step>) -> Step {
+        Step::Repeat {
+            repeat: times,
+            steps,
+        }
+    }
+
+    pub fn when(flag: &str, steps: Vec<Step>) -> Step {
+        Step::When {
+            when: flag.to_string(),
+            steps,
+        }
+    }
+}
+
+impl TryFrom<Value> for Step {
+    type Error = String;
+
+    fn try_from(value: Value) -> Result<Step, String> {
+        let expected = "expected a part or a table with `steps` and `repeat` or `when`";
+        let mut table = match value {
+            Value::String(name) => return name.parse().map(Step::Part),
+            Value::Table(table) => table,
+            _ => return Err(expected.to_string()),
+        };
+        let steps = match table.remove("steps") {
+            Some(Value::Array(steps)) => steps
+                .into_iter()
+                .map(Step::try_from)
+                .collect::<Result<Vec<_>, _>>()?,
+            _ => return Err(format!("{}, `steps` must be a list", expected)),
+        };
+        let step = match (table.remove("repeat"), table.remove("when")) {
+            (Some(Value::Integer(times)), None) if times >= 0 => {
+                Step::repeat(times as usize, steps)
+            }
+            (Some(_), None) => return Err("`repeat` must be a count".to_string()),
+            (None, Some(Value::String(flag))) => Step::when(&flag, steps),
+            (None, Some(_)) => return Err("`when` must be the name of a flag".to_string()),
+            _ => return Err(expected.to_string()),
+        };
+        match table.keys().next() {
+            Some(key) => Err(format!("unknown key `{}` in step", key)),
+            None => Ok(step),
+        }
+    }
+}
+
+impl From<Part> for Step {
+    fn from(part: Part) -> Step {
+        Step::Part(part)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Recipe {
+    pub name: String,
+    pub steps: Vec<Step>,
+}
+
+impl Recipe {
+    pub fn new(name: &str, steps: Vec<Step>) -> Recipe {
+        Recipe {
+            name: name.to_string(),
+            steps,
+        }
+    }
+
+    /// Reads the `[[recipe]]` tables of a TOML file.
+    pub fn load_file<P: AsRef<Path>>(path: P) -> Result<Vec<Recipe>, RecipeError> {
+        Recipe::load(&fs::read_to_string(path)?)
+    }
+
+    pub fn load(source: &str) -> Result<Vec<Recipe>, RecipeError> {
+        #[derive(Deserialize)]
+        struct RecipeFile {
+            #[serde(default)]
+            recipe: Vec<Recipe>,
+        }
+        let file: RecipeFile = toml::from_str(source).map_err(|e| RecipeError::Parse {
+            line: e
+                .span()
+                .map_or(1, |span| source[..span.start].matches('\n').count() + 1),
+            message: e.message().to_string(),
+        })?;
+        Ok(file.recipe)
+    }
+}
+
+#[derive(Debug)]
+pub enum RecipeError {
+    Io(io::Error),
+    Parse { line: usize, message: String },
+}
+
+impl fmt::Display for RecipeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RecipeError::Io(e) => write!(f, "cannot read recipes: {}", e),
+            RecipeError::Parse { line, message } => write!(f, "line {}: {}", line, message),
+        }
+    }
+}
+
+impl Error for RecipeError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            RecipeError::Io(e) => Some(e),
+            RecipeError::Parse { .. } => None,
+        }
+    }
+}
+
+impl From<io::Error> for RecipeError {
+    fn from(e: io::Error) -> RecipeError {
+        RecipeError::Io(e)
+    }
+}
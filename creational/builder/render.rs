@@ -0,0 +1,72 @@
+//! Renderers writing a `Product` to any `io::Write`, e.g. a file, a
+//! `Vec<u8>` or stdout.
+
+use std::io;
+
+use crate::Product;
+
+pub trait Render {
+    fn render(&self, product: &Product, out: &mut dyn io::Write) -> io::Result<()>;
+}
+
+/// The starred banner of `Product::list_parts`.
+pub struct Banner;
+
+impl Render for Banner {
+    fn render(&self, product: &Product, out: &mut dyn io::Write) -> io::Result<()> {
+        let parts_list = String::from(" parts ");
+        writeln!(out, "{0}{1}{0}", "*".repeat(10), parts_list)?;
+        for name in product.names() {
+            writeln!(out, "{}", name)?;
+        }
+        writeln!(
+            out,
+            "{0}{1}{0}",
+            "*".repeat(10),
+            "*".repeat(parts_list.len())
+        )
+    }
+}
+
+/// One part name per line.
+pub struct PlainList;
+
+impl Render for PlainList {
+    fn render(&self, product: &Product, out: &mut dyn io::Write) -> io::Result<()> {
+        for name in product.names() {
+            writeln!(out, "- {}", name)?;
+        }
+        Ok(())
+    }
+}
+
+pub struct MarkdownTable;
+
+impl Render for MarkdownTable {
+    fn render(&self, product: &Product, out: &mut dyn io::Write) -> io::Result<()> {
+        writeln!(out, "| # | Part | Name |")?;
+        writeln!(out, "|---|------|------|")?;
+        for (i, (part, name)) in product.parts.iter().enumerate() {
+            writeln!(
+                out,
+                "| {} | {} | {} |",
+                i + 1,
+                part,
+                name.replace('|', "\\|")
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// The JSON form `Product::from_json` reads back.
+#[cfg(feature = "serialize")]
+pub struct Json;
+
+#[cfg(feature = "serialize")]
+impl Render for Json {
+    fn render(&self, product: &Product, out: &mut dyn io::Write) -> io::Result<()> {
+        serde_json::to_writer_pretty(&mut *out, product)?;
+        writeln!(out)
+    }
+}
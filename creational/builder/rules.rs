@@ -0,0 +1,174 @@
+//! Validation rules a builder checks its product against before handing it
+//! over. Every broken rule is reported, not just the first one.
+
+use std::error::Error;
+use std::fmt;
+use std::rc::Rc;
+
+use crate::{Part, Product};
+
+#[derive(Clone)]
+enum Rule {
+    Required(Part),
+    Unique(Part),
+    Before(Part, Part),
+    Custom {
+        description: String,
+        check: Rc<dyn Fn(&Product) -> bool>,
+    },
+}
+
+#[derive(Clone, Default)]
+pub struct Rules {
+    rules: Vec<Rule>,
+}
+
+impl Rules {
+    pub fn new() -> Rules {
+        Rules::default()
+    }
+
+    /// The product must have at least one `part`.
+    pub fn require(mut self, part: Part) -> Rules {
+        self.rules.push(Rule::Required(part));
+        self
+    }
+
+    /// The product may have at most one `part`.
+    pub fn unique(mut self, part: Part) -> Rules {
+        self.rules.push(Rule::Unique(part));
+        self
+    }
+
+    /// Every `first` part must come before every `then` part.
+    pub fn before(mut self, first: Part, then: Part) -> Rules {
+        self.rules.push(Rule::Before(first, then));
+        self
+    }
+
+    /// The product must satisfy `check`, reported by `description` otherwise.
+    pub fn check<F>(mut self, description: &str, check: F) -> Rules
+    where
+        F: Fn(&Product) -> bool + 'static,
+    {
+        self.rules.push(Rule::Custom {
+            description: description.to_string(),
+            check: Rc::new(check),
+        });
+        self
+    }
+
+    pub fn validate(&self, product: &Product) -> Result<(), BuildError> {
+        let positions = |part: Part| -> Vec<usize> {
+            product
+                .parts
+                .iter()
+                .enumerate()
+                .filter(|(_, (kind, _))| *kind == part)
+                .map(|(i, _)| i)
+                .collect()
+        };
+        let mut violations = Vec::new();
+        for rule in &self.rules {
+            match rule {
+                Rule::Required(part) => {
+                    if positions(*part).is_empty() {
+                        violations.push(Violation::Missing(*part));
+                    }
+                }
+                Rule::Unique(part) => {
+                    let count = positions(*part).len();
+                    if count > 1 {
+                        violations.push(Violation::Duplicate { part: *part, count });
+                    }
+                }
+                Rule::Before(first, then) => {
+                    let last_first = positions(*first).last().copied();
+                    let first_then = positions(*then).first().copied();
+                    if let (Some(last), Some(then_at)) = (last_first, first_then) {
+                        if last > then_at {
+                            violations.push(Violation::Order {
+                                first: *first,
+                                then: *then,
+                            });
+                        }
+                    }
+                }
+                Rule::Custom { description, check } => {
+                    if !check(product) {
+                        violations.push(Violation::Custom(description.clone()));
+                    }
+                }
+            }
+        }
+        if violations.is_empty() {
+            Ok(())
+        } else {
+            Err(BuildError { violations })
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Violation {
+    Missing(Part),
+    Duplicate {
+        part: Part,
+        count: usize,
+    },
+    Order {
+        first: Part,
+        then: Part,
+    },
+    Custom(String),
+    /// Producing the part failed.
+    Failed {
+        part: Part,
+        message: String,
+    },
+    /// An edit named a part the product does not have.
+    NoSuchPart {
+        edit: &'static str,
+        name: String,
+    },
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Violation::Missing(part) => write!(f, "part {} is required", part),
+            Violation::Duplicate { part, count } => {
+                write!(
+                    f,
+                    "part {} appears {} times, at most once allowed",
+                    part, count
+                )
+            }
+            Violation::Order { first, then } => {
+                write!(f, "part {} must come before part {}", first, then)
+            }
+            Violation::Custom(description) => write!(f, "{}", description),
+            Violation::Failed { part, message } => write!(f, "part {} failed: {}", part, message),
+            Violation::NoSuchPart { edit, name } => {
+                write!(f, "cannot {} `{}`: no such part", edit, name)
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct BuildError {
+    pub violations: Vec<Violation>,
+}
+
+impl fmt::Display for BuildError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "invalid product:")?;
+        for violation in &self.violations {
+            write!(f, "\n  {}", violation)?;
+        }
+        Ok(())
+    }
+}
+
+impl Error for BuildError {}
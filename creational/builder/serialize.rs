@@ -0,0 +1,71 @@
+//! Saving and loading products, with the `serialize` feature. Both formats
+//! list the parts in order, each with its kind and name:
+//!
+//! ```toml
+//! [[parts]]
+//! part = "a"
+//! name = "part a1"
+//! ```
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::{Part, Product};
+
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct PartEntry {
+    part: Part,
+    name: String,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ProductFile {
+    #[serde(default)]
+    parts: Vec<PartEntry>,
+}
+
+impl Serialize for Product {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let parts = self
+            .parts
+            .iter()
+            .map(|(part, name)| PartEntry {
+                part: *part,
+                name: name.clone(),
+            })
+            .collect();
+        ProductFile { parts }.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Product {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Product, D::Error> {
+        let file = ProductFile::deserialize(deserializer)?;
+        Ok(Product {
+            parts: file
+                .parts
+                .into_iter()
+                .map(|entry| (entry.part, entry.name))
+                .collect(),
+        })
+    }
+}
+
+impl Product {
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        serde_json::to_string(self)
+    }
+
+    pub fn from_json(source: &str) -> serde_json::Result<Product> {
+        serde_json::from_str(source)
+    }
+
+    pub fn to_toml(&self) -> Result<String, toml::ser::Error> {
+        toml::to_string(self)
+    }
+
+    pub fn from_toml(source: &str) -> Result<Product, toml::de::Error> {
+        toml::from_str(source)
+    }
+}
@@ -0,0 +1,83 @@
+//! A builder tracking its required parts in its type. `build` only exists
+//! once parts a and b are set, so an incomplete product does not compile:
+//!
+//! ```ignore
+//! // error[E0599]: no method named `build` found for struct
+//! // `TypestateBuilder<Present, Missing>`
+//! let product = TypestateBuilder::new().part_a("engine").build();
+//! ```
+//!
+//! No test checks this: doctests are not run for the example binaries, so
+//! the snippet is only marked `ignore`. Try it in `main` to see the error.
+//!
+//! Part c is optional and can be added in any state, any number of times.
+
+use std::marker::PhantomData;
+
+use crate::{Part, Product};
+
+/// States of a required part.
+pub struct Missing;
+pub struct Present;
+
+pub struct TypestateBuilder<A, B> {
+    part_a: Option<String>,
+    part_b: Option<String>,
+    extras: Vec<String>,
+    state: PhantomData<(A, B)>,
+}
+
+impl TypestateBuilder<Missing, Missing> {
+    pub fn new() -> Self {
+        TypestateBuilder {
+            part_a: None,
+            part_b: None,
+            extras: Vec::new(),
+            state: PhantomData,
+        }
+    }
+}
+
+impl<A, B> TypestateBuilder<A, B> {
+    /// Moves the parts into a builder of another state.
+    fn into_state<A2, B2>(self) -> TypestateBuilder<A2, B2> {
+        TypestateBuilder {
+            part_a: self.part_a,
+            part_b: self.part_b,
+            extras: self.extras,
+            state: PhantomData,
+        }
+    }
+
+    pub fn part_c(mut self, part: &str) -> Self {
+        self.extras.push(part.to_string());
+        self
+    }
+}
+
+impl<B> TypestateBuilder<Missing, B> {
+    pub fn part_a(mut self, part: &str) -> TypestateBuilder<Present, B> {
+        self.part_a = Some(part.to_string());
+        self.into_state()
+    }
+}
+
+impl<A> TypestateBuilder<A, Missing> {
+    pub fn part_b(mut self, part: &str) -> TypestateBuilder<A, Present> {
+        self.part_b = Some(part.to_string());
+        self.into_state()
+    }
+}
+
+impl TypestateBuilder<Present, Present> {
+    /// The required parts come first, whatever order they were set in.
+    pub fn build(self) -> Product {
+        let mut product = Product::new();
+        product.add(Part::A, &self.part_a.unwrap());
+        product.add(Part::B, &self.part_b.unwrap());
+        for part in &self.extras {
+            product.add(Part::C, part);
+        }
+        product
+    }
+}
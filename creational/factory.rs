@@ -1,44 +1,862 @@
 //! Factory method creational design pattern allows creating objects without having to specify the exact type of the object that will be created.
 
-trait Shape {
-    fn draw(&self);
+use std::collections::HashMap;
+use std::error::Error;
+use std::f64::consts::PI;
+use std::fmt;
+use std::io;
+
+#[path = "factory/canvas.rs"]
+mod canvas;
+#[path = "factory/collision.rs"]
+mod collision;
+#[path = "factory/loader.rs"]
+mod loader;
+#[path = "factory/plugin.rs"]
+mod plugin;
+#[path = "factory/render.rs"]
+mod render;
+#[path = "factory/scene.rs"]
+mod scene;
+#[path = "factory/svg.rs"]
+mod svg;
+#[path = "factory/transform.rs"]
+mod transform;
+
+use canvas::{Canvas, FillMode};
+use collision::{intersects, Collider, SpatialIndex};
+use render::{Renderer, Style};
+use scene::Scene;
+use shape_plugin_api::{PluginDeclaration, ABI_VERSION};
+use transform::{Group, Transform, Transformed};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Point {
+    x: f64,
+    y: f64,
 }
 
-enum ShapeType {
-    Rectangle,
-    Circle,
+impl Point {
+    fn new(x: f64, y: f64) -> Point {
+        Point { x, y }
+    }
+    fn distance(&self, other: Point) -> f64 {
+        (self.x - other.x).hypot(self.y - other.y)
+    }
 }
 
-struct Rectangle {}
+/// Axis-aligned box enclosing a shape. The box of no points at all, e.g. of
+/// an empty group, is empty: its `min` lies past its `max`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct BoundingBox {
+    min: Point,
+    max: Point,
+}
+
+impl BoundingBox {
+    fn from_points(points: &[Point]) -> BoundingBox {
+        let mut bbox = BoundingBox {
+            min: Point::new(f64::INFINITY, f64::INFINITY),
+            max: Point::new(f64::NEG_INFINITY, f64::NEG_INFINITY),
+        };
+        for p in points {
+            bbox.min = Point::new(bbox.min.x.min(p.x), bbox.min.y.min(p.y));
+            bbox.max = Point::new(bbox.max.x.max(p.x), bbox.max.y.max(p.y));
+        }
+        bbox
+    }
+    fn is_empty(&self) -> bool {
+        self.min.x > self.max.x || self.min.y > self.max.y
+    }
+    fn width(&self) -> f64 {
+        self.max.x - self.min.x
+    }
+    fn height(&self) -> f64 {
+        self.max.y - self.min.y
+    }
+    /// The box enclosing both, ignoring an empty one.
+    fn union(&self, other: &BoundingBox) -> BoundingBox {
+        match (self.is_empty(), other.is_empty()) {
+            (true, _) => *other,
+            (_, true) => *self,
+            _ => BoundingBox::from_points(&[self.min, self.max, other.min, other.max]),
+        }
+    }
+    fn intersects(&self, other: &BoundingBox) -> bool {
+        self.min.x <= other.max.x
+            && other.min.x <= self.max.x
+            && self.min.y <= other.max.y
+            && other.min.y <= self.max.y
+    }
+}
+
+trait Shape {
+    fn draw(&self, renderer: &mut dyn Renderer, style: &Style);
+    fn area(&self) -> f64;
+    fn perimeter(&self) -> f64;
+    fn bounding_box(&self) -> BoundingBox;
+    fn contains(&self, p: Point) -> bool;
+    /// The outline used for intersection tests against other shapes.
+    fn collider(&self) -> Collider;
+}
+
+struct Rectangle {
+    origin: Point,
+    width: f64,
+    height: f64,
+}
 
 impl Shape for Rectangle {
-    fn draw(&self) {
-        println!("draw a rectangle!");
+    fn draw(&self, renderer: &mut dyn Renderer, style: &Style) {
+        renderer.rect(self.origin, self.width, self.height, style);
+    }
+    fn area(&self) -> f64 {
+        self.width * self.height
+    }
+    fn perimeter(&self) -> f64 {
+        2.0 * (self.width + self.height)
+    }
+    fn bounding_box(&self) -> BoundingBox {
+        BoundingBox {
+            min: self.origin,
+            max: Point::new(self.origin.x + self.width, self.origin.y + self.height),
+        }
+    }
+    fn contains(&self, p: Point) -> bool {
+        let bbox = self.bounding_box();
+        p.x >= bbox.min.x && p.x <= bbox.max.x && p.y >= bbox.min.y && p.y <= bbox.max.y
+    }
+    fn collider(&self) -> Collider {
+        let bbox = self.bounding_box();
+        Collider::Polygon(vec![
+            bbox.min,
+            Point::new(bbox.max.x, bbox.min.y),
+            bbox.max,
+            Point::new(bbox.min.x, bbox.max.y),
+        ])
     }
 }
 
-struct Circle {}
+struct Circle {
+    center: Point,
+    radius: f64,
+}
 
 impl Shape for Circle {
-    fn draw(&self) {
-        println!("draw a circle!");
+    fn draw(&self, renderer: &mut dyn Renderer, style: &Style) {
+        renderer.circle(self.center, self.radius, style);
+    }
+    fn area(&self) -> f64 {
+        PI * self.radius * self.radius
+    }
+    fn perimeter(&self) -> f64 {
+        2.0 * PI * self.radius
+    }
+    fn bounding_box(&self) -> BoundingBox {
+        BoundingBox {
+            min: Point::new(self.center.x - self.radius, self.center.y - self.radius),
+            max: Point::new(self.center.x + self.radius, self.center.y + self.radius),
+        }
+    }
+    fn contains(&self, p: Point) -> bool {
+        self.center.distance(p) <= self.radius
+    }
+    fn collider(&self) -> Collider {
+        Collider::Circle {
+            center: self.center,
+            radius: self.radius,
+        }
     }
 }
 
-struct ShapeFactory;
-impl ShapeFactory {
-    fn new_shape(s: &ShapeType) -> Box<dyn Shape> {
-        match s {
-            ShapeType::Circle => Box::new(Circle {}),
-            ShapeType::Rectangle => Box::new(Rectangle {}),
+struct Ellipse {
+    center: Point,
+    rx: f64,
+    ry: f64,
+}
+
+impl Shape for Ellipse {
+    fn draw(&self, renderer: &mut dyn Renderer, style: &Style) {
+        renderer.ellipse(self.center, self.rx, self.ry, style);
+    }
+    fn area(&self) -> f64 {
+        PI * self.rx * self.ry
+    }
+    // Ramanujan's approximation, there is no closed form.
+    fn perimeter(&self) -> f64 {
+        let (a, b) = (self.rx, self.ry);
+        PI * (3.0 * (a + b) - ((3.0 * a + b) * (a + 3.0 * b)).sqrt())
+    }
+    fn bounding_box(&self) -> BoundingBox {
+        BoundingBox {
+            min: Point::new(self.center.x - self.rx, self.center.y - self.ry),
+            max: Point::new(self.center.x + self.rx, self.center.y + self.ry),
+        }
+    }
+    fn contains(&self, p: Point) -> bool {
+        let dx = (p.x - self.center.x) / self.rx;
+        let dy = (p.y - self.center.y) / self.ry;
+        dx * dx + dy * dy <= 1.0
+    }
+    fn collider(&self) -> Collider {
+        Collider::ellipse(self.center, self.rx, self.ry)
+    }
+}
+
+/// A simple (non self-intersecting) polygon given by its vertices in order.
+struct Polygon {
+    points: Vec<Point>,
+}
+
+impl Polygon {
+    /// A regular polygon with `sides` vertices on a circle of `radius`.
+    fn regular(center: Point, sides: usize, radius: f64) -> Polygon {
+        let points = (0..sides)
+            .map(|i| {
+                let angle = 2.0 * PI * i as f64 / sides as f64;
+                Point::new(
+                    center.x + radius * angle.cos(),
+                    center.y + radius * angle.sin(),
+                )
+            })
+            .collect();
+        Polygon { points }
+    }
+    fn edges(&self) -> impl Iterator<Item = (Point, Point)> + '_ {
+        edges(&self.points)
+    }
+}
+
+/// The closed sequence of edges of a polygon, last vertex back to the first.
+fn edges(points: &[Point]) -> impl Iterator<Item = (Point, Point)> + '_ {
+    let next = points.iter().cycle().skip(1);
+    points.iter().copied().zip(next.copied())
+}
+
+// Even-odd ray casting.
+fn polygon_contains(points: &[Point], p: Point) -> bool {
+    let mut inside = false;
+    for (a, b) in edges(points) {
+        if (a.y > p.y) != (b.y > p.y) && p.x < (b.x - a.x) * (p.y - a.y) / (b.y - a.y) + a.x {
+            inside = !inside;
+        }
+    }
+    inside
+}
+
+impl Shape for Polygon {
+    fn draw(&self, renderer: &mut dyn Renderer, style: &Style) {
+        renderer.polygon(&self.points, style);
+    }
+    // Shoelace formula.
+    fn area(&self) -> f64 {
+        let twice: f64 = self.edges().map(|(a, b)| a.x * b.y - b.x * a.y).sum();
+        twice.abs() / 2.0
+    }
+    fn perimeter(&self) -> f64 {
+        self.edges().map(|(a, b)| a.distance(b)).sum()
+    }
+    fn bounding_box(&self) -> BoundingBox {
+        BoundingBox::from_points(&self.points)
+    }
+    fn contains(&self, p: Point) -> bool {
+        polygon_contains(&self.points, p)
+    }
+    fn collider(&self) -> Collider {
+        Collider::Polygon(self.points.clone())
+    }
+}
+
+struct Triangle {
+    polygon: Polygon,
+}
+
+impl Triangle {
+    fn new(a: Point, b: Point, c: Point) -> Triangle {
+        Triangle {
+            polygon: Polygon {
+                points: vec![a, b, c],
+            },
+        }
+    }
+}
+
+impl Shape for Triangle {
+    fn draw(&self, renderer: &mut dyn Renderer, style: &Style) {
+        self.polygon.draw(renderer, style);
+    }
+    fn area(&self) -> f64 {
+        self.polygon.area()
+    }
+    fn perimeter(&self) -> f64 {
+        self.polygon.perimeter()
+    }
+    fn bounding_box(&self) -> BoundingBox {
+        self.polygon.bounding_box()
+    }
+    fn contains(&self, p: Point) -> bool {
+        self.polygon.contains(p)
+    }
+    fn collider(&self) -> Collider {
+        self.polygon.collider()
+    }
+}
+
+/// Errors reported by `ShapeFactory::create`.
+#[derive(Debug, PartialEq)]
+enum FactoryError {
+    UnknownShape(String),
+    MissingParam {
+        shape: String,
+        param: String,
+    },
+    InvalidParam {
+        shape: String,
+        param: String,
+        reason: String,
+    },
+    Plugin {
+        path: String,
+        reason: String,
+    },
+    PluginAbiMismatch {
+        path: String,
+        expected: u32,
+        found: u32,
+    },
+}
+
+impl fmt::Display for FactoryError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FactoryError::UnknownShape(name) => write!(f, "unknown shape `{}`", name),
+            FactoryError::MissingParam { shape, param } => {
+                write!(f, "shape `{}` requires parameter `{}`", shape, param)
+            }
+            FactoryError::InvalidParam {
+                shape,
+                param,
+                reason,
+            } => write!(
+                f,
+                "invalid parameter `{}` for shape `{}`: {}",
+                param, shape, reason
+            ),
+            FactoryError::Plugin { path, reason } => {
+                write!(f, "cannot load plugin `{}`: {}", path, reason)
+            }
+            FactoryError::PluginAbiMismatch {
+                path,
+                expected,
+                found,
+            } => write!(
+                f,
+                "plugin `{}` uses shape ABI version {}, expected version {}",
+                path, found, expected
+            ),
         }
     }
 }
 
+impl Error for FactoryError {}
+
+#[derive(Debug, Clone, PartialEq)]
+enum ParamValue {
+    Number(f64),
+    Points(Vec<Point>),
+}
+
+/// Named parameters handed to a shape constructor.
+#[derive(Default)]
+struct ShapeParams {
+    shape: String,
+    values: HashMap<String, ParamValue>,
+}
+
+impl ShapeParams {
+    fn new() -> ShapeParams {
+        ShapeParams::default()
+    }
+    fn with(self, name: &str, value: f64) -> ShapeParams {
+        self.with_value(name, ParamValue::Number(value))
+    }
+    fn with_points(self, name: &str, points: Vec<Point>) -> ShapeParams {
+        self.with_value(name, ParamValue::Points(points))
+    }
+    fn with_value(mut self, name: &str, value: ParamValue) -> ShapeParams {
+        self.values.insert(name.to_string(), value);
+        self
+    }
+    fn get(&self, name: &str) -> Result<f64, FactoryError> {
+        match self.values.get(name) {
+            Some(ParamValue::Number(v)) => Ok(*v),
+            Some(_) => Err(self.invalid(name, "expected a number")),
+            None => Err(self.missing(name)),
+        }
+    }
+    fn get_or(&self, name: &str, default: f64) -> Result<f64, FactoryError> {
+        match self.values.get(name) {
+            None => Ok(default),
+            Some(_) => self.get(name),
+        }
+    }
+    /// Like `get`, but the value must be strictly positive.
+    fn get_positive(&self, name: &str) -> Result<f64, FactoryError> {
+        let v = self.get(name)?;
+        if v > 0.0 {
+            Ok(v)
+        } else {
+            Err(self.invalid(name, "must be positive"))
+        }
+    }
+    fn get_points(&self, name: &str) -> Result<&[Point], FactoryError> {
+        match self.values.get(name) {
+            Some(ParamValue::Points(points)) => Ok(points),
+            Some(_) => Err(self.invalid(name, "expected a list of points")),
+            None => Err(self.missing(name)),
+        }
+    }
+    fn missing(&self, name: &str) -> FactoryError {
+        FactoryError::MissingParam {
+            shape: self.shape.clone(),
+            param: name.to_string(),
+        }
+    }
+    fn invalid(&self, name: &str, reason: &str) -> FactoryError {
+        FactoryError::InvalidParam {
+            shape: self.shape.clone(),
+            param: name.to_string(),
+            reason: reason.to_string(),
+        }
+    }
+}
+
+type ShapeConstructor = Box<dyn Fn(&ShapeParams) -> Result<Box<dyn Shape>, FactoryError>>;
+
+/// The factory keeps a registry of constructors keyed by shape name, so new
+/// shapes can be added at runtime without touching the factory itself.
+struct ShapeFactory {
+    constructors: HashMap<String, ShapeConstructor>,
+}
+
+impl ShapeFactory {
+    /// Creates a factory with the built-in shapes already registered.
+    fn new() -> ShapeFactory {
+        let mut factory = ShapeFactory {
+            constructors: HashMap::new(),
+        };
+        factory.register("rectangle", |params| {
+            Ok(Box::new(Rectangle {
+                origin: Point::new(params.get_or("x", 0.0)?, params.get_or("y", 0.0)?),
+                width: params.get_positive("width")?,
+                height: params.get_positive("height")?,
+            }))
+        });
+        factory.register("circle", |params| {
+            Ok(Box::new(Circle {
+                center: Point::new(params.get_or("x", 0.0)?, params.get_or("y", 0.0)?),
+                radius: params.get_positive("radius")?,
+            }))
+        });
+        factory.register("ellipse", |params| {
+            Ok(Box::new(Ellipse {
+                center: Point::new(params.get_or("x", 0.0)?, params.get_or("y", 0.0)?),
+                rx: params.get_positive("rx")?,
+                ry: params.get_positive("ry")?,
+            }))
+        });
+        factory.register("triangle", |params| match params.get_points("points")? {
+            [a, b, c] => Ok(Box::new(Triangle::new(*a, *b, *c))),
+            _ => Err(params.invalid("points", "expected exactly 3 points")),
+        });
+        factory.register("polygon", |params| {
+            let points = params.get_points("points")?;
+            if points.len() < 3 {
+                return Err(params.invalid("points", "expected at least 3 points"));
+            }
+            Ok(Box::new(Polygon {
+                points: points.to_vec(),
+            }))
+        });
+        factory
+    }
+
+    /// Registers a constructor under `name`, replacing any previous one.
+    fn register<F>(&mut self, name: &str, constructor: F)
+    where
+        F: Fn(&ShapeParams) -> Result<Box<dyn Shape>, FactoryError> + 'static,
+    {
+        self.constructors
+            .insert(name.to_string(), Box::new(constructor));
+    }
+
+    fn create(&self, name: &str, params: ShapeParams) -> Result<Box<dyn Shape>, FactoryError> {
+        let constructor = self
+            .constructors
+            .get(name)
+            .ok_or_else(|| FactoryError::UnknownShape(name.to_string()))?;
+        constructor(&ShapeParams {
+            shape: name.to_string(),
+            ..params
+        })
+    }
+}
+
 fn main() {
-    let shape = ShapeFactory::new_shape(&ShapeType::Circle);
-    shape.draw(); // output: draw a circle!
+    let mut factory = ShapeFactory::new();
+
+    let circle = factory
+        .create("circle", ShapeParams::new().with("radius", 1.0))
+        .unwrap();
+    assert!((circle.area() - PI).abs() < 1e-9);
+    assert!(circle.contains(Point::new(0.5, 0.5)));
+
+    let rect = factory
+        .create(
+            "rectangle",
+            ShapeParams::new()
+                .with("x", 1.0)
+                .with("y", 2.0)
+                .with("width", 4.0)
+                .with("height", 3.0),
+        )
+        .unwrap();
+    assert_eq!(rect.area(), 12.0);
+    assert_eq!(rect.perimeter(), 14.0);
+    let bbox = rect.bounding_box();
+    assert_eq!((bbox.width(), bbox.height()), (4.0, 3.0));
+    assert!(!rect.contains(Point::new(0.0, 0.0)));
+
+    let triangle = factory
+        .create(
+            "triangle",
+            ShapeParams::new().with_points(
+                "points",
+                vec![
+                    Point::new(0.0, 0.0),
+                    Point::new(4.0, 0.0),
+                    Point::new(0.0, 3.0),
+                ],
+            ),
+        )
+        .unwrap();
+    assert_eq!(triangle.area(), 6.0);
+    assert_eq!(triangle.perimeter(), 12.0);
+    assert!(triangle.contains(Point::new(1.0, 1.0)));
+    assert!(!triangle.contains(Point::new(3.0, 3.0)));
+
+    let ellipse = factory
+        .create(
+            "ellipse",
+            ShapeParams::new().with("rx", 2.0).with("ry", 1.0),
+        )
+        .unwrap();
+    assert!((ellipse.area() - 2.0 * PI).abs() < 1e-9);
+
+    // Downstream code registers its own shapes, here built from a regular polygon.
+    factory.register("hexagon", |params| {
+        Ok(Box::new(Polygon::regular(
+            Point::new(params.get_or("x", 0.0)?, params.get_or("y", 0.0)?),
+            6,
+            params.get_positive("side")?,
+        )))
+    });
+    let hexagon = factory
+        .create("hexagon", ShapeParams::new().with("side", 2.0))
+        .unwrap();
+    assert!((hexagon.perimeter() - 12.0).abs() < 1e-9);
+
+    let mut scene = Scene::new();
+    scene.add(circle, Style::new().fill("gold"));
+    scene.add(rect, Style::new().fill("steelblue").stroke("navy", 0.5));
+    scene.add(triangle, Style::new());
+    scene.add(ellipse, Style::new().fill("pink"));
+    scene.add(hexagon, Style::new().stroke("green", 0.25));
+    scene.write_svg(&mut io::stdout()).unwrap();
+    // output:
+    /*
+    <svg xmlns="http://www.w3.org/2000/svg" viewBox="-2 -1.732 7 6.732" width="7" height="6.732">
+      <circle cx="0" cy="0" r="1" fill="gold" stroke="black" stroke-width="1"/>
+      <rect x="1" y="2" width="4" height="3" fill="steelblue" stroke="navy" stroke-width="0.5"/>
+      <polygon points="0,0 4,0 0,3" fill="none" stroke="black" stroke-width="1"/>
+      <ellipse cx="0" cy="0" rx="2" ry="1" fill="pink" stroke="black" stroke-width="1"/>
+      <polygon points="2,0 1,1.732 -1,1.732 -2,0 -1,-1.732 1,-1.732" fill="none" stroke="green" stroke-width="0.25"/>
+    </svg>
+    */
+
+    let mut scene = Scene::new();
+    let params = [
+        (
+            "rectangle",
+            ShapeParams::new()
+                .with("x", 1.0)
+                .with("y", 1.0)
+                .with("width", 10.0)
+                .with("height", 5.0),
+        ),
+        (
+            "circle",
+            ShapeParams::new()
+                .with("x", 18.0)
+                .with("y", 4.5)
+                .with("radius", 4.0),
+        ),
+        (
+            "triangle",
+            ShapeParams::new().with_points(
+                "points",
+                vec![
+                    Point::new(24.0, 8.0),
+                    Point::new(31.0, 8.0),
+                    Point::new(27.5, 1.0),
+                ],
+            ),
+        ),
+    ];
+    for (name, params) in params {
+        scene.add(factory.create(name, params).unwrap(), Style::new());
+    }
+    let mut canvas = Canvas::new(32, 9);
+    scene.render(&mut canvas);
+    print!("{}", canvas);
+    assert_eq!(
+        canvas.to_string(),
+        include_str!("factory/golden/canvas_filled.txt")
+    );
+    let mut canvas = Canvas::new(32, 9).mode(FillMode::Outlined).glyph('*');
+    scene.render(&mut canvas);
+    print!("{}", canvas);
+    assert_eq!(
+        canvas.to_string(),
+        include_str!("factory/golden/canvas_outlined.txt")
+    );
+    let mut canvas = Canvas::new(0, 3);
+    scene.render(&mut canvas);
+    assert_eq!(canvas.to_string(), "\n\n\n");
+
+    let scene = factory
+        .load_scene_file(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/creational/factory/scenes/example.toml"
+        ))
+        .unwrap();
+    let bbox = scene.bounding_box().unwrap();
+    assert_eq!((bbox.width(), bbox.height()), (12.0, 8.0));
+
+    let source = "[[shape]]\ntype = \"circle\"\nradius = 1\n\n[[shape]]\ntype = \"octagon\"\n";
+    if let Err(e) = factory.load_scene(source) {
+        println!("{}", e); // output: line 6: unknown shape `octagon`
+    }
+    let source = "[[shape]]\ntype = \"rectangle\"\nwidth = 2\nheight = \"tall\"\n";
+    if let Err(e) = factory.load_scene(source) {
+        println!("{}", e); // output: line 4: `height` must be a number or a list of [x, y] points
+    }
+    let source = "[[shape]]\ntype = \"circle\"\nradius = -2\n";
+    if let Err(e) = factory.load_scene(source) {
+        println!("{}", e); // output: line 3: invalid parameter `radius` for shape `circle`: must be positive
+    }
+
+    // A house built from a group, placed twice: once moved to the right and
+    // once more scaled down and tipped over inside a nested group.
+    let house = |factory: &ShapeFactory| {
+        let walls = ShapeParams::new().with("width", 4.0).with("height", 3.0);
+        let roof = ShapeParams::new().with_points(
+            "points",
+            vec![
+                Point::new(-1.0, 0.0),
+                Point::new(5.0, 0.0),
+                Point::new(2.0, -2.0),
+            ],
+        );
+        Group::new(Transform::IDENTITY)
+            .add(factory.create("rectangle", walls).unwrap())
+            .add(factory.create("triangle", roof).unwrap())
+    };
+    let moved = Transformed::new(Box::new(house(&factory)), Transform::translate(10.0, 2.0));
+    let bbox = moved.bounding_box();
+    assert_eq!(
+        (bbox.min, bbox.max),
+        (Point::new(9.0, 0.0), Point::new(15.0, 5.0))
+    );
+    assert!(moved.contains(Point::new(12.0, 4.0)));
+    assert_eq!(moved.area(), 18.0);
+
+    let tipped = Group::new(Transform::translate(20.0, 0.0)).add(Box::new(
+        Group::new(Transform::scale(0.5, 0.5).then(&Transform::rotate(PI / 2.0)))
+            .add(Box::new(house(&factory))),
+    ));
+    let bbox = tipped.bounding_box();
+    assert!((bbox.min.x - 18.5).abs() < 1e-9 && (bbox.max.x - 21.0).abs() < 1e-9);
+    assert!((bbox.min.y + 0.5).abs() < 1e-9 && (bbox.max.y - 2.5).abs() < 1e-9);
+    assert!(tipped.contains(Point::new(19.0, 1.0)));
+    assert!((tipped.area() - 18.0 / 4.0).abs() < 1e-9);
+
+    let mut scene = Scene::new();
+    scene.add(Box::new(moved), Style::new().fill("wheat"));
+    scene.add(Box::new(tipped), Style::new().fill("salmon"));
+    scene.write_svg(&mut io::stdout()).unwrap();
+    // output:
+    /*
+    <svg xmlns="http://www.w3.org/2000/svg" viewBox="9 -0.5 12 5.5" width="12" height="5.5">
+      <g transform="matrix(1 0 0 1 10 2)">
+        <g transform="matrix(1 0 0 1 0 0)">
+          <rect x="0" y="0" width="4" height="3" fill="wheat" stroke="black" stroke-width="1"/>
+          <polygon points="-1,0 5,0 2,-2" fill="wheat" stroke="black" stroke-width="1"/>
+        </g>
+      </g>
+      <g transform="matrix(1 0 0 1 20 0)">
+        <g transform="matrix(0 0.5 -0.5 0 0 0)">
+          <g transform="matrix(1 0 0 1 0 0)">
+            <rect x="0" y="0" width="4" height="3" fill="salmon" stroke="black" stroke-width="1"/>
+            <polygon points="-1,0 5,0 2,-2" fill="salmon" stroke="black" stroke-width="1"/>
+          </g>
+        </g>
+      </g>
+    </svg>
+    */
+    let mut canvas = Canvas::new(22, 6);
+    scene.render(&mut canvas);
+    print!("{}", canvas);
+
+    // An empty group has an empty box, which leaves the scene's box as it is.
+    let before = scene.bounding_box();
+    scene.add(Box::new(Group::new(Transform::IDENTITY)), Style::new());
+    scene.add(
+        Box::new(Transformed::new(
+            Box::new(Group::new(Transform::IDENTITY)),
+            Transform::rotate(PI / 4.0),
+        )),
+        Style::new(),
+    );
+    assert_eq!(scene.bounding_box(), before);
+    let mut empty = Scene::new();
+    empty.add(Box::new(Group::new(Transform::IDENTITY)), Style::new());
+    assert_eq!(empty.bounding_box(), None);
+
+    // Hitboxes for a small editor: which shapes are under the cursor and
+    // which ones overlap.
+    let mut hitboxes = Scene::new();
+    let params = [
+        (
+            "rectangle",
+            ShapeParams::new().with("width", 4.0).with("height", 4.0),
+        ),
+        (
+            "circle",
+            ShapeParams::new()
+                .with("x", 5.0)
+                .with("y", 2.0)
+                .with("radius", 1.5),
+        ),
+        (
+            "triangle",
+            ShapeParams::new().with_points(
+                "points",
+                vec![
+                    Point::new(10.0, 0.0),
+                    Point::new(14.0, 0.0),
+                    Point::new(12.0, 3.0),
+                ],
+            ),
+        ),
+        (
+            "ellipse",
+            ShapeParams::new()
+                .with("x", 12.0)
+                .with("y", 5.0)
+                .with("rx", 3.0)
+                .with("ry", 1.0),
+        ),
+    ];
+    for (name, params) in params {
+        hitboxes.add(factory.create(name, params).unwrap(), Style::new());
+    }
+    hitboxes.add(
+        Box::new(Transformed::new(
+            factory
+                .create(
+                    "rectangle",
+                    ShapeParams::new().with("width", 2.0).with("height", 2.0),
+                )
+                .unwrap(),
+            Transform::rotate(PI / 4.0).then(&Transform::translate(20.0, 0.0)),
+        )),
+        Style::new(),
+    );
+    let index = SpatialIndex::new(&hitboxes, 4.0);
+    assert_eq!(index.shapes_at(Point::new(3.8, 2.0)), vec![0, 1]);
+    assert_eq!(index.shapes_at(Point::new(12.0, 2.5)), vec![2]);
+    assert_eq!(index.shapes_at(Point::new(20.0, 1.0)), vec![4]);
+    assert!(index.shapes_at(Point::new(21.2, 0.2)).is_empty());
+    assert_eq!(index.overlapping_pairs(), vec![(0, 1)]);
+    let shapes: Vec<&dyn Shape> = hitboxes.shapes().collect();
+    assert!(intersects(shapes[0], shapes[1]));
+    assert!(!intersects(shapes[2], shapes[3]));
+    assert!(!Collider::Polygon(Vec::new()).intersects(&shapes[0].collider()));
 
-    let shape = ShapeFactory::new_shape(&ShapeType::Rectangle);
-    shape.draw(); // output: draw a rectangle!
+    // Shapes spanning too many cells are checked by every query instead.
+    let mut large = Scene::new();
+    for (name, params) in [
+        (
+            "rectangle",
+            ShapeParams::new().with("width", 1e9).with("height", 1e9),
+        ),
+        ("circle", ShapeParams::new().with("radius", 1.0)),
+    ] {
+        large.add(factory.create(name, params).unwrap(), Style::new());
+    }
+    let index = SpatialIndex::new(&large, 0.001);
+    assert_eq!(index.shapes_at(Point::new(0.5, 0.5)), vec![0, 1]);
+    assert_eq!(index.overlapping_pairs(), vec![(0, 1)]);
+
+    // Shape packs are shared libraries built from creational/factory/plugins,
+    // run `cargo build --workspace` first.
+    let plugin = std::env::var("SHAPE_PLUGIN").unwrap_or_else(|_| {
+        format!(
+            "{}/target/debug/{}star_shapes{}",
+            env!("CARGO_MANIFEST_DIR"),
+            std::env::consts::DLL_PREFIX,
+            std::env::consts::DLL_SUFFIX
+        )
+    });
+    match unsafe { factory.load_plugin(&plugin) } {
+        Ok(names) => {
+            println!("loaded plugin shapes: {:?}", names); // output: loaded plugin shapes: ["star"]
+            let star = factory
+                .create("star", ShapeParams::new().with("outer", 2.0))
+                .unwrap();
+            assert!(star.contains(Point::new(0.0, 0.0)));
+            assert!(!star.contains(Point::new(1.9, 0.0)));
+            let bbox = star.bounding_box();
+            assert!((bbox.height() - (2.0 + 2.0 * (0.3 * PI).sin())).abs() < 1e-9);
+            if let Err(e) = factory.create("star", ShapeParams::new()) {
+                println!("{}", e); // output: shape `star` requires parameter `outer`
+            }
+            let params = ShapeParams::new().with("outer", 2.0).with("tips", 1e18);
+            if let Err(e) = factory.create("star", params) {
+                println!("{}", e); // output: invalid parameter `tips` for shape `star`: rejected by the plugin
+            }
+        }
+        Err(e) => println!("{}", e),
+    }
+    // A plugin built against another version of the API is refused.
+    let outdated = PluginDeclaration {
+        abi_version: ABI_VERSION + 1,
+        name: b"old_shapes\0".as_ptr().cast(),
+        shapes: std::ptr::null(),
+        shape_count: 0,
+    };
+    if let Err(e) = plugin::check_abi("libold_shapes.so", &outdated) {
+        println!("{}", e); // output: plugin `libold_shapes.so` uses shape ABI version 2, expected version 1
+    }
+
+    if let Err(e) = factory.create("hexagon", ShapeParams::new()) {
+        println!("{}", e); // output: shape `hexagon` requires parameter `side`
+    }
+    if let Err(e) = factory.create("circle", ShapeParams::new().with("radius", -1.0)) {
+        println!("{}", e); // output: invalid parameter `radius` for shape `circle`: must be positive
+    }
+    if let Err(e) = factory.create("octagon", ShapeParams::new()) {
+        println!("{}", e); // output: unknown shape `octagon`
+    }
 }
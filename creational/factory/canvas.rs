@@ -0,0 +1,110 @@
+//! Character grid backend for the `Renderer` trait, for drawing shapes in a terminal.
+
+use std::fmt;
+
+use crate::render::{Renderer, Style};
+use crate::transform::Transform;
+use crate::{Circle, Ellipse, Point, Polygon, Rectangle, Shape};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FillMode {
+    Filled,
+    Outlined,
+}
+
+/// A `width` x `height` grid where each cell covers one unit square, with
+/// the cell in row 0, column 0 spanning (0, 0) to (1, 1).
+pub struct Canvas {
+    width: usize,
+    height: usize,
+    cells: Vec<char>,
+    mode: FillMode,
+    glyph: char,
+    transforms: Vec<Transform>,
+}
+
+impl Canvas {
+    pub fn new(width: usize, height: usize) -> Canvas {
+        Canvas {
+            width,
+            height,
+            cells: vec![' '; width * height],
+            mode: FillMode::Filled,
+            glyph: '#',
+            transforms: Vec::new(),
+        }
+    }
+    pub fn mode(mut self, mode: FillMode) -> Canvas {
+        self.mode = mode;
+        self
+    }
+    pub fn glyph(mut self, glyph: char) -> Canvas {
+        self.glyph = glyph;
+        self
+    }
+
+    /// Marks every cell whose center is covered by `shape`. In outlined mode
+    /// only cells with an uncovered neighbour are marked.
+    fn rasterize(&mut self, shape: &dyn Shape) {
+        let current = self.transforms.last().copied().unwrap_or_default();
+        let inverse = match current.inverse() {
+            Some(inverse) => inverse,
+            None => return,
+        };
+        let inside = |p: Point| shape.contains(inverse.apply(p));
+        for row in 0..self.height {
+            for col in 0..self.width {
+                let center = Point::new(col as f64 + 0.5, row as f64 + 0.5);
+                if !inside(center) {
+                    continue;
+                }
+                let edge = [(1.0, 0.0), (-1.0, 0.0), (0.0, 1.0), (0.0, -1.0)]
+                    .iter()
+                    .any(|(dx, dy)| !inside(Point::new(center.x + dx, center.y + dy)));
+                if self.mode == FillMode::Filled || edge {
+                    self.cells[row * self.width + col] = self.glyph;
+                }
+            }
+        }
+    }
+}
+
+impl Renderer for Canvas {
+    fn rect(&mut self, origin: Point, width: f64, height: f64, _style: &Style) {
+        self.rasterize(&Rectangle {
+            origin,
+            width,
+            height,
+        });
+    }
+    fn circle(&mut self, center: Point, radius: f64, _style: &Style) {
+        self.rasterize(&Circle { center, radius });
+    }
+    fn ellipse(&mut self, center: Point, rx: f64, ry: f64, _style: &Style) {
+        self.rasterize(&Ellipse { center, rx, ry });
+    }
+    fn polygon(&mut self, points: &[Point], _style: &Style) {
+        self.rasterize(&Polygon {
+            points: points.to_vec(),
+        });
+    }
+    fn push_transform(&mut self, transform: &Transform) {
+        let current = self.transforms.last().copied().unwrap_or_default();
+        self.transforms.push(transform.then(&current));
+    }
+    fn pop_transform(&mut self) {
+        self.transforms.pop();
+    }
+}
+
+/// One line per row, with trailing blanks removed.
+impl fmt::Display for Canvas {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for y in 0..self.height {
+            let row = &self.cells[y * self.width..(y + 1) * self.width];
+            let line: String = row.iter().collect();
+            writeln!(f, "{}", line.trim_end())?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,213 @@
+//! Intersection tests between shapes and a spatial index answering hit-test
+//! queries over a scene.
+
+use std::collections::{BTreeSet, HashMap};
+use std::f64::consts::PI;
+
+use crate::scene::Scene;
+use crate::transform::Transform;
+use crate::{edges, polygon_contains, BoundingBox, Point, Shape};
+
+/// Number of segments used when a curve has to be approximated by a polygon.
+const CURVE_SEGMENTS: usize = 32;
+
+/// Shapes spanning more grid cells than this are kept out of the grid and
+/// checked by every query instead.
+const MAX_CELLS_PER_SHAPE: i128 = 4096;
+
+/// The outline of a shape as used for intersection tests.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Collider {
+    Circle { center: Point, radius: f64 },
+    Polygon(Vec<Point>),
+    Compound(Vec<Collider>),
+}
+
+impl Collider {
+    /// An ellipse approximated by a polygon with `CURVE_SEGMENTS` sides.
+    pub fn ellipse(center: Point, rx: f64, ry: f64) -> Collider {
+        let points = (0..CURVE_SEGMENTS)
+            .map(|i| {
+                let angle = 2.0 * PI * i as f64 / CURVE_SEGMENTS as f64;
+                Point::new(center.x + rx * angle.cos(), center.y + ry * angle.sin())
+            })
+            .collect();
+        Collider::Polygon(points)
+    }
+
+    /// Circles stay circles under translations, rotations and uniform
+    /// scales, and become polygons otherwise.
+    pub fn transformed(&self, t: &Transform) -> Collider {
+        match self {
+            Collider::Circle { center, radius } if t.is_similarity() => Collider::Circle {
+                center: t.apply(*center),
+                radius: radius * t.length_scale(),
+            },
+            Collider::Circle { center, radius } => {
+                Collider::ellipse(*center, *radius, *radius).transformed(t)
+            }
+            Collider::Polygon(points) => {
+                Collider::Polygon(points.iter().map(|p| t.apply(*p)).collect())
+            }
+            Collider::Compound(parts) => {
+                Collider::Compound(parts.iter().map(|c| c.transformed(t)).collect())
+            }
+        }
+    }
+
+    pub fn intersects(&self, other: &Collider) -> bool {
+        use Collider::*;
+        match (self, other) {
+            (Compound(parts), other) | (other, Compound(parts)) => {
+                parts.iter().any(|part| part.intersects(other))
+            }
+            (
+                Circle { center, radius },
+                Circle {
+                    center: c2,
+                    radius: r2,
+                },
+            ) => center.distance(*c2) <= radius + r2,
+            (Circle { center, radius }, Polygon(points))
+            | (Polygon(points), Circle { center, radius }) => {
+                polygon_contains(points, *center)
+                    || edges(points).any(|(a, b)| segment_distance(*center, a, b) <= *radius)
+            }
+            // An empty polygon, as a plugin outline may be, touches nothing.
+            (Polygon(a), Polygon(b)) => {
+                edges(a).any(|(p1, p2)| edges(b).any(|(q1, q2)| segments_cross(p1, p2, q1, q2)))
+                    || b.first().is_some_and(|p| polygon_contains(a, *p))
+                    || a.first().is_some_and(|p| polygon_contains(b, *p))
+            }
+        }
+    }
+}
+
+/// Whether two shapes overlap or touch.
+pub fn intersects(a: &dyn Shape, b: &dyn Shape) -> bool {
+    a.bounding_box().intersects(&b.bounding_box()) && a.collider().intersects(&b.collider())
+}
+
+fn segment_distance(p: Point, a: Point, b: Point) -> f64 {
+    let (dx, dy) = (b.x - a.x, b.y - a.y);
+    let len2 = dx * dx + dy * dy;
+    if len2 == 0.0 {
+        return p.distance(a);
+    }
+    let t = (((p.x - a.x) * dx + (p.y - a.y) * dy) / len2).clamp(0.0, 1.0);
+    p.distance(Point::new(a.x + t * dx, a.y + t * dy))
+}
+
+fn segments_cross(p1: Point, p2: Point, q1: Point, q2: Point) -> bool {
+    let orient =
+        |a: Point, b: Point, c: Point| (b.x - a.x) * (c.y - a.y) - (b.y - a.y) * (c.x - a.x);
+    let on_segment = |a: Point, b: Point, c: Point| {
+        c.x >= a.x.min(b.x) && c.x <= a.x.max(b.x) && c.y >= a.y.min(b.y) && c.y <= a.y.max(b.y)
+    };
+    let (d1, d2) = (orient(q1, q2, p1), orient(q1, q2, p2));
+    let (d3, d4) = (orient(p1, p2, q1), orient(p1, p2, q2));
+    if ((d1 > 0.0 && d2 < 0.0) || (d1 < 0.0 && d2 > 0.0))
+        && ((d3 > 0.0 && d4 < 0.0) || (d3 < 0.0 && d4 > 0.0))
+    {
+        return true;
+    }
+    (d1 == 0.0 && on_segment(q1, q2, p1))
+        || (d2 == 0.0 && on_segment(q1, q2, p2))
+        || (d3 == 0.0 && on_segment(p1, p2, q1))
+        || (d4 == 0.0 && on_segment(p1, p2, q2))
+}
+
+/// A uniform grid over the bounding boxes of a scene's shapes. Shapes are
+/// identified by their position in the scene.
+pub struct SpatialIndex<'a> {
+    shapes: Vec<&'a dyn Shape>,
+    colliders: Vec<Collider>,
+    boxes: Vec<BoundingBox>,
+    cell_size: f64,
+    cells: HashMap<(i64, i64), Vec<usize>>,
+    /// Shapes too large for the grid, or without a finite bounding box.
+    oversized: Vec<usize>,
+}
+
+impl<'a> SpatialIndex<'a> {
+    /// # Panics
+    ///
+    /// If `cell_size` is not a positive, finite number.
+    pub fn new(scene: &'a Scene, cell_size: f64) -> SpatialIndex<'a> {
+        assert!(
+            cell_size.is_finite() && cell_size > 0.0,
+            "cell size must be positive and finite, got {}",
+            cell_size
+        );
+        let shapes: Vec<&dyn Shape> = scene.shapes().collect();
+        let mut index = SpatialIndex {
+            colliders: shapes.iter().map(|s| s.collider()).collect(),
+            boxes: shapes.iter().map(|s| s.bounding_box()).collect(),
+            shapes,
+            cell_size,
+            cells: HashMap::new(),
+            oversized: Vec::new(),
+        };
+        for (id, bbox) in index.boxes.iter().enumerate() {
+            let (min, max) = (index.cell(bbox.min), index.cell(bbox.max));
+            let count = |min: i64, max: i64| (max as i128 - min as i128 + 1).max(0);
+            let finite = [bbox.min.x, bbox.min.y, bbox.max.x, bbox.max.y]
+                .iter()
+                .all(|v| v.is_finite());
+            if !finite || count(min.0, max.0) * count(min.1, max.1) > MAX_CELLS_PER_SHAPE {
+                index.oversized.push(id);
+                continue;
+            }
+            for x in min.0..=max.0 {
+                for y in min.1..=max.1 {
+                    index.cells.entry((x, y)).or_default().push(id);
+                }
+            }
+        }
+        index
+    }
+
+    fn cell(&self, p: Point) -> (i64, i64) {
+        (
+            (p.x / self.cell_size).floor() as i64,
+            (p.y / self.cell_size).floor() as i64,
+        )
+    }
+
+    /// Shapes containing `p`, in scene order.
+    pub fn shapes_at(&self, p: Point) -> Vec<usize> {
+        let cell = self.cells.get(&self.cell(p)).map_or(&[][..], |ids| ids);
+        let mut ids: Vec<usize> = cell
+            .iter()
+            .chain(&self.oversized)
+            .copied()
+            .filter(|&id| self.shapes[id].contains(p))
+            .collect();
+        ids.sort_unstable();
+        ids
+    }
+
+    /// Every pair of overlapping shapes, as `(lower id, higher id)` in order.
+    pub fn overlapping_pairs(&self) -> Vec<(usize, usize)> {
+        let mut candidates = BTreeSet::new();
+        for ids in self.cells.values() {
+            for (i, &a) in ids.iter().enumerate() {
+                for &b in &ids[i + 1..] {
+                    candidates.insert((a.min(b), a.max(b)));
+                }
+            }
+        }
+        for &a in &self.oversized {
+            for b in (0..self.shapes.len()).filter(|&b| b != a) {
+                candidates.insert((a.min(b), a.max(b)));
+            }
+        }
+        candidates
+            .into_iter()
+            .filter(|&(a, b)| {
+                self.boxes[a].intersects(&self.boxes[b])
+                    && self.colliders[a].intersects(&self.colliders[b])
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,185 @@
+//! Builds a whole `Scene` from a TOML description, one `[[shape]]` table per shape:
+//!
+//! ```toml
+//! [[shape]]
+//! type = "circle"
+//! x = 4
+//! radius = 2.5
+//! fill = "gold"
+//! ```
+//!
+//! `type` names a shape registered in the `ShapeFactory`, `fill`, `stroke` and
+//! `stroke_width` set the style, and every other key is passed on as a shape
+//! parameter.
+
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::ops::Range;
+use std::path::Path;
+
+use serde::Deserialize;
+use toml::{Spanned, Value};
+
+use crate::render::Style;
+use crate::scene::Scene;
+use crate::{FactoryError, ParamValue, Point, ShapeFactory, ShapeParams};
+
+type ShapeTable = BTreeMap<Spanned<String>, Spanned<Value>>;
+
+#[derive(Deserialize)]
+struct SceneFile {
+    #[serde(default)]
+    shape: Vec<Spanned<ShapeTable>>,
+}
+
+#[derive(Debug)]
+pub enum SceneError {
+    Io(io::Error),
+    Parse { line: usize, message: String },
+    Shape { line: usize, source: FactoryError },
+}
+
+impl fmt::Display for SceneError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SceneError::Io(e) => write!(f, "cannot read scene: {}", e),
+            SceneError::Parse { line, message } => write!(f, "line {}: {}", line, message),
+            SceneError::Shape { line, source } => write!(f, "line {}: {}", line, source),
+        }
+    }
+}
+
+impl Error for SceneError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            SceneError::Io(e) => Some(e),
+            SceneError::Parse { .. } => None,
+            SceneError::Shape { source, .. } => Some(source),
+        }
+    }
+}
+
+impl From<io::Error> for SceneError {
+    fn from(e: io::Error) -> SceneError {
+        SceneError::Io(e)
+    }
+}
+
+impl ShapeFactory {
+    pub fn load_scene_file<P: AsRef<Path>>(&self, path: P) -> Result<Scene, SceneError> {
+        self.load_scene(&fs::read_to_string(path)?)
+    }
+
+    pub fn load_scene(&self, source: &str) -> Result<Scene, SceneError> {
+        let line = |span: Range<usize>| source[..span.start].matches('\n').count() + 1;
+        let file: SceneFile = toml::from_str(source).map_err(|e| SceneError::Parse {
+            line: e.span().map(line).unwrap_or(1),
+            message: e.message().to_string(),
+        })?;
+
+        let mut scene = Scene::new();
+        for table in &file.shape {
+            let entry = ShapeEntry::read(table).map_err(|(span, message)| SceneError::Parse {
+                line: line(span),
+                message,
+            })?;
+            let params = entry
+                .params
+                .iter()
+                .fold(ShapeParams::new(), |params, (name, value, _)| {
+                    params.with_value(name, value.clone())
+                });
+            let shape = self.create(&entry.kind, params).map_err(|source| {
+                // Point at the offending value when there is one, at the
+                // `[[shape]]` table otherwise.
+                let span = match &source {
+                    FactoryError::UnknownShape(_) => entry.kind_span.clone(),
+                    FactoryError::InvalidParam { param, .. } => entry
+                        .params
+                        .iter()
+                        .find(|(name, _, _)| name == param)
+                        .map_or(table.span(), |(_, _, span)| span.clone()),
+                    _ => table.span(),
+                };
+                SceneError::Shape {
+                    line: line(span),
+                    source,
+                }
+            })?;
+            scene.add(shape, entry.style);
+        }
+        Ok(scene)
+    }
+}
+
+struct ShapeEntry {
+    kind: String,
+    kind_span: Range<usize>,
+    style: Style,
+    params: Vec<(String, ParamValue, Range<usize>)>,
+}
+
+impl ShapeEntry {
+    /// Splits a `[[shape]]` table into its type, style and parameters.
+    fn read(table: &Spanned<ShapeTable>) -> Result<ShapeEntry, (Range<usize>, String)> {
+        let mut kind = None;
+        let mut style = Style::new();
+        let mut params = Vec::new();
+        for (key, value) in table.get_ref() {
+            let invalid = |expected: &str| {
+                Err((
+                    value.span(),
+                    format!("`{}` must be {}", key.get_ref(), expected),
+                ))
+            };
+            match (key.get_ref().as_str(), value.get_ref()) {
+                ("type", Value::String(name)) => kind = Some((name.clone(), value.span())),
+                ("fill", Value::String(color)) => style.fill = Some(color.clone()),
+                ("stroke", Value::String(color)) => style.stroke = Some(color.clone()),
+                ("type", _) | ("fill", _) | ("stroke", _) => return invalid("a string"),
+                ("stroke_width", v) => match number(v) {
+                    Some(width) => style.stroke_width = width,
+                    None => return invalid("a number"),
+                },
+                (name, v) => match param(v) {
+                    Some(p) => params.push((name.to_string(), p, value.span())),
+                    None => return invalid("a number or a list of [x, y] points"),
+                },
+            }
+        }
+        let (kind, kind_span) =
+            kind.ok_or_else(|| (table.span(), "shape is missing `type`".to_string()))?;
+        Ok(ShapeEntry {
+            kind,
+            kind_span,
+            style,
+            params,
+        })
+    }
+}
+
+fn number(v: &Value) -> Option<f64> {
+    match v {
+        Value::Integer(i) => Some(*i as f64),
+        Value::Float(f) => Some(*f),
+        _ => None,
+    }
+}
+
+fn param(v: &Value) -> Option<ParamValue> {
+    if let Some(n) = number(v) {
+        return Some(ParamValue::Number(n));
+    }
+    let points = v
+        .as_array()?
+        .iter()
+        .map(|p| match p.as_array()?.as_slice() {
+            [x, y] => Some(Point::new(number(x)?, number(y)?)),
+            _ => None,
+        })
+        .collect::<Option<Vec<Point>>>()?;
+    Some(ParamValue::Points(points))
+}
@@ -0,0 +1,140 @@
+//! Loads extra shape constructors from shared libraries at runtime. The ABI
+//! between the host and plugins lives in the `shape_plugin_api` crate, see
+//! `creational/factory/plugins/star_shapes` for an example plugin.
+
+use std::ffi::{CStr, CString};
+use std::path::Path;
+use std::ptr;
+use std::rc::Rc;
+
+use libloading::Library;
+use shape_plugin_api::{
+    CreateStatus, PluginDeclaration, RawParam, RawParams, RawPoint, RawShape, ShapeVTable,
+    ABI_VERSION, ENTRY_SYMBOL,
+};
+
+use crate::collision::Collider;
+use crate::render::{Renderer, Style};
+use crate::{BoundingBox, FactoryError, ParamValue, Point, Shape, ShapeFactory};
+
+/// A shape created and owned by a plugin. It keeps the library loaded for
+/// as long as it lives.
+pub struct PluginShape {
+    raw: RawShape,
+    _library: Rc<Library>,
+}
+
+impl PluginShape {
+    fn vtable(&self) -> &ShapeVTable {
+        unsafe { &*self.raw.vtable }
+    }
+    fn outline(&self) -> Vec<Point> {
+        let outline = self.vtable().outline;
+        let len = outline(self.raw.data, ptr::null_mut(), 0);
+        let mut points = vec![RawPoint { x: 0.0, y: 0.0 }; len];
+        outline(self.raw.data, points.as_mut_ptr(), len);
+        points.iter().map(|p| Point::new(p.x, p.y)).collect()
+    }
+}
+
+impl Shape for PluginShape {
+    fn draw(&self, renderer: &mut dyn Renderer, style: &Style) {
+        renderer.polygon(&self.outline(), style);
+    }
+    fn area(&self) -> f64 {
+        (self.vtable().area)(self.raw.data)
+    }
+    fn perimeter(&self) -> f64 {
+        (self.vtable().perimeter)(self.raw.data)
+    }
+    fn bounding_box(&self) -> BoundingBox {
+        BoundingBox::from_points(&self.outline())
+    }
+    fn contains(&self, p: Point) -> bool {
+        (self.vtable().contains)(self.raw.data, RawPoint { x: p.x, y: p.y })
+    }
+    fn collider(&self) -> Collider {
+        Collider::Polygon(self.outline())
+    }
+}
+
+impl Drop for PluginShape {
+    fn drop(&mut self) {
+        (self.vtable().drop)(self.raw.data);
+    }
+}
+
+/// Refuses a plugin built against another version of `shape_plugin_api`,
+/// before anything else of its declaration is read.
+pub fn check_abi(path: &str, decl: &PluginDeclaration) -> Result<(), FactoryError> {
+    if decl.abi_version == ABI_VERSION {
+        Ok(())
+    } else {
+        Err(FactoryError::PluginAbiMismatch {
+            path: path.to_string(),
+            expected: ABI_VERSION,
+            found: decl.abi_version,
+        })
+    }
+}
+
+impl ShapeFactory {
+    /// Registers every shape exported by the plugin at `path` and returns
+    /// their names. Only numeric parameters are passed on to plugin shapes.
+    ///
+    /// # Safety
+    ///
+    /// Loading a library runs its initialisation code, and the library must
+    /// export a `PluginDeclaration` built with `shape_plugin_api`.
+    pub unsafe fn load_plugin<P: AsRef<Path>>(
+        &mut self,
+        path: P,
+    ) -> Result<Vec<String>, FactoryError> {
+        let path = path.as_ref().display().to_string();
+        let plugin_error = |reason: String| FactoryError::Plugin {
+            path: path.clone(),
+            reason,
+        };
+        let library = Rc::new(Library::new(&path).map_err(|e| plugin_error(e.to_string()))?);
+        let decl: &PluginDeclaration = &**library
+            .get::<*const PluginDeclaration>(ENTRY_SYMBOL)
+            .map_err(|e| plugin_error(e.to_string()))?;
+        check_abi(&path, decl)?;
+
+        let shapes = std::slice::from_raw_parts(decl.shapes, decl.shape_count);
+        let mut names = Vec::new();
+        for shape in shapes {
+            let name = CStr::from_ptr(shape.name).to_string_lossy().into_owned();
+            let create = shape.create;
+            let library = library.clone();
+            self.register(&name, move |params| {
+                let names: Vec<(CString, f64)> = params
+                    .values
+                    .iter()
+                    .filter_map(|(name, value)| match value {
+                        ParamValue::Number(v) => Some((CString::new(name.as_str()).ok()?, *v)),
+                        ParamValue::Points(_) => None,
+                    })
+                    .collect();
+                let raw: Vec<RawParam> = names
+                    .iter()
+                    .map(|(name, value)| RawParam::new(name, *value))
+                    .collect();
+                let result = create(RawParams::new(&raw));
+                let param = || unsafe { CStr::from_ptr(result.param) }.to_string_lossy();
+                match result.status {
+                    CreateStatus::Ok => Ok(Box::new(PluginShape {
+                        raw: result.shape,
+                        _library: library.clone(),
+                    })),
+                    CreateStatus::MissingParam => Err(params.missing(&param())),
+                    CreateStatus::InvalidParam => {
+                        Err(params.invalid(&param(), "rejected by the plugin"))
+                    }
+                }
+            });
+            names.push(name);
+        }
+        Ok(names)
+    }
+}
@@ -0,0 +1,228 @@
+//! The C ABI shared by the factory example and shape plugins loaded from
+//! shared libraries.
+//!
+//! A plugin exports a `PluginDeclaration` static named by `ENTRY_SYMBOL`,
+//! usually through `declare_shape_plugin!`. The host reads `abi_version`
+//! first and refuses the plugin unless it equals `ABI_VERSION`, so every
+//! layout change in this crate must bump that constant.
+
+use std::ffi::{c_char, c_void, CStr};
+use std::marker::PhantomData;
+
+pub const ABI_VERSION: u32 = 1;
+
+/// Name of the exported `PluginDeclaration` static.
+pub const ENTRY_SYMBOL: &[u8] = b"shape_plugin_declaration\0";
+
+#[repr(C)]
+pub struct PluginDeclaration {
+    /// Must stay the first field, see the crate docs.
+    pub abi_version: u32,
+    pub name: *const c_char,
+    pub shapes: *const ShapeDecl,
+    pub shape_count: usize,
+}
+
+// The declaration only points at static data.
+unsafe impl Sync for PluginDeclaration {}
+
+#[repr(C)]
+pub struct ShapeDecl {
+    pub name: *const c_char,
+    pub create: extern "C" fn(RawParams) -> CreateResult,
+}
+
+unsafe impl Sync for ShapeDecl {}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RawPoint {
+    pub x: f64,
+    pub y: f64,
+}
+
+#[repr(C)]
+pub struct RawParam<'a> {
+    name: *const c_char,
+    value: f64,
+    _name: PhantomData<&'a CStr>,
+}
+
+impl<'a> RawParam<'a> {
+    pub fn new(name: &'a CStr, value: f64) -> RawParam<'a> {
+        RawParam {
+            name: name.as_ptr(),
+            value,
+            _name: PhantomData,
+        }
+    }
+}
+
+/// Numeric parameters passed from the host to a shape constructor.
+#[repr(C)]
+pub struct RawParams<'a> {
+    ptr: *const RawParam<'a>,
+    len: usize,
+    _params: PhantomData<&'a [RawParam<'a>]>,
+}
+
+impl<'a> RawParams<'a> {
+    pub fn new(params: &'a [RawParam<'a>]) -> RawParams<'a> {
+        RawParams {
+            ptr: params.as_ptr(),
+            len: params.len(),
+            _params: PhantomData,
+        }
+    }
+
+    pub fn get(&self, name: &str) -> Option<f64> {
+        // Both pointers come from borrowed data, see the constructors.
+        let params = unsafe { std::slice::from_raw_parts(self.ptr, self.len) };
+        params
+            .iter()
+            .find(|p| unsafe { CStr::from_ptr(p.name) }.to_bytes() == name.as_bytes())
+            .map(|p| p.value)
+    }
+}
+
+/// The table of functions the host calls on a plugin-owned shape.
+#[repr(C)]
+pub struct ShapeVTable {
+    pub area: extern "C" fn(*const c_void) -> f64,
+    pub perimeter: extern "C" fn(*const c_void) -> f64,
+    pub contains: extern "C" fn(*const c_void, RawPoint) -> bool,
+    /// Copies at most `cap` outline vertices into `out` and returns the
+    /// total number of vertices.
+    pub outline: extern "C" fn(*const c_void, *mut RawPoint, usize) -> usize,
+    pub drop: extern "C" fn(*mut c_void),
+}
+
+/// A shape owned by the plugin. The host must call `vtable.drop` exactly once.
+#[repr(C)]
+pub struct RawShape {
+    pub data: *mut c_void,
+    pub vtable: *const ShapeVTable,
+}
+
+/// Safe side of the ABI for plugin authors.
+pub trait PluginShape: 'static {
+    fn area(&self) -> f64;
+    fn perimeter(&self) -> f64;
+    fn contains(&self, p: RawPoint) -> bool;
+    fn outline(&self) -> Vec<RawPoint>;
+}
+
+struct VTableFor<T>(PhantomData<T>);
+
+impl<T: PluginShape> VTableFor<T> {
+    const VTABLE: ShapeVTable = ShapeVTable {
+        area: area::<T>,
+        perimeter: perimeter::<T>,
+        contains: contains::<T>,
+        outline: outline::<T>,
+        drop: drop::<T>,
+    };
+}
+
+extern "C" fn area<T: PluginShape>(data: *const c_void) -> f64 {
+    unsafe { &*(data as *const T) }.area()
+}
+
+extern "C" fn perimeter<T: PluginShape>(data: *const c_void) -> f64 {
+    unsafe { &*(data as *const T) }.perimeter()
+}
+
+extern "C" fn contains<T: PluginShape>(data: *const c_void, p: RawPoint) -> bool {
+    unsafe { &*(data as *const T) }.contains(p)
+}
+
+extern "C" fn outline<T: PluginShape>(
+    data: *const c_void,
+    out: *mut RawPoint,
+    cap: usize,
+) -> usize {
+    let points = unsafe { &*(data as *const T) }.outline();
+    for (i, p) in points.iter().take(cap).enumerate() {
+        unsafe { out.add(i).write(*p) };
+    }
+    points.len()
+}
+
+extern "C" fn drop<T: PluginShape>(data: *mut c_void) {
+    std::mem::drop(unsafe { Box::from_raw(data as *mut T) });
+}
+
+impl RawShape {
+    pub fn new<T: PluginShape>(shape: T) -> RawShape {
+        RawShape {
+            data: Box::into_raw(Box::new(shape)) as *mut c_void,
+            vtable: &VTableFor::<T>::VTABLE,
+        }
+    }
+}
+
+#[repr(u32)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CreateStatus {
+    Ok = 0,
+    MissingParam = 1,
+    InvalidParam = 2,
+}
+
+#[repr(C)]
+pub struct CreateResult {
+    pub status: CreateStatus,
+    /// The offending parameter when `status` is not `Ok`.
+    pub param: *const c_char,
+    /// Only valid when `status` is `Ok`.
+    pub shape: RawShape,
+}
+
+impl CreateResult {
+    pub fn ok<T: PluginShape>(shape: T) -> CreateResult {
+        CreateResult {
+            status: CreateStatus::Ok,
+            param: std::ptr::null(),
+            shape: RawShape::new(shape),
+        }
+    }
+    pub fn missing(param: &'static CStr) -> CreateResult {
+        CreateResult::error(CreateStatus::MissingParam, param)
+    }
+    pub fn invalid(param: &'static CStr) -> CreateResult {
+        CreateResult::error(CreateStatus::InvalidParam, param)
+    }
+    fn error(status: CreateStatus, param: &'static CStr) -> CreateResult {
+        CreateResult {
+            status,
+            param: param.as_ptr(),
+            shape: RawShape {
+                data: std::ptr::null_mut(),
+                vtable: std::ptr::null(),
+            },
+        }
+    }
+}
+
+/// Exports the plugin entry point:
+///
+/// ```ignore
+/// declare_shape_plugin!(c"my_shapes", [(c"blob", create_blob)]);
+/// ```
+#[macro_export]
+macro_rules! declare_shape_plugin {
+    ($name:expr, [$(($shape:expr, $create:path)),* $(,)?]) => {
+        #[no_mangle]
+        pub static shape_plugin_declaration: $crate::PluginDeclaration = {
+            const SHAPES: &[$crate::ShapeDecl] = &[
+                $($crate::ShapeDecl { name: $shape.as_ptr(), create: $create }),*
+            ];
+            $crate::PluginDeclaration {
+                abi_version: $crate::ABI_VERSION,
+                name: $name.as_ptr(),
+                shapes: SHAPES.as_ptr(),
+                shape_count: SHAPES.len(),
+            }
+        };
+    };
+}
@@ -0,0 +1,81 @@
+//! An example shape pack for the factory example, built as a shared library.
+
+use std::f64::consts::PI;
+
+use shape_plugin_api::{declare_shape_plugin, CreateResult, PluginShape, RawParams, RawPoint};
+
+/// More tips than this are refused, as the outline is built eagerly.
+const MAX_TIPS: f64 = 1000.0;
+
+struct Star {
+    points: Vec<RawPoint>,
+}
+
+impl Star {
+    fn new(center: RawPoint, tips: usize, outer: f64, inner: f64) -> Star {
+        let points = (0..tips * 2)
+            .map(|i| {
+                let radius = if i % 2 == 0 { outer } else { inner };
+                let angle = PI * i as f64 / tips as f64 - PI / 2.0;
+                RawPoint {
+                    x: center.x + radius * angle.cos(),
+                    y: center.y + radius * angle.sin(),
+                }
+            })
+            .collect();
+        Star { points }
+    }
+
+    fn edges(&self) -> impl Iterator<Item = (RawPoint, RawPoint)> + '_ {
+        let next = self.points.iter().cycle().skip(1);
+        self.points.iter().copied().zip(next.copied())
+    }
+}
+
+impl PluginShape for Star {
+    fn area(&self) -> f64 {
+        let twice: f64 = self.edges().map(|(a, b)| a.x * b.y - b.x * a.y).sum();
+        twice.abs() / 2.0
+    }
+    fn perimeter(&self) -> f64 {
+        self.edges()
+            .map(|(a, b)| (a.x - b.x).hypot(a.y - b.y))
+            .sum()
+    }
+    fn contains(&self, p: RawPoint) -> bool {
+        let mut inside = false;
+        for (a, b) in self.edges() {
+            if (a.y > p.y) != (b.y > p.y) && p.x < (b.x - a.x) * (p.y - a.y) / (b.y - a.y) + a.x {
+                inside = !inside;
+            }
+        }
+        inside
+    }
+    fn outline(&self) -> Vec<RawPoint> {
+        self.points.clone()
+    }
+}
+
+extern "C" fn create_star(params: RawParams) -> CreateResult {
+    let center = RawPoint {
+        x: params.get("x").unwrap_or(0.0),
+        y: params.get("y").unwrap_or(0.0),
+    };
+    let tips = params.get("tips").unwrap_or(5.0);
+    if !(3.0..=MAX_TIPS).contains(&tips) || tips.fract() != 0.0 {
+        return CreateResult::invalid(c"tips");
+    }
+    let outer = match params.get("outer") {
+        Some(r) if r > 0.0 => r,
+        Some(_) => return CreateResult::invalid(c"outer"),
+        None => return CreateResult::missing(c"outer"),
+    };
+    let inner = match params.get("inner") {
+        Some(r) if r > 0.0 && r < outer => r,
+        Some(_) => return CreateResult::invalid(c"inner"),
+        None => outer / 2.0,
+    };
+    CreateResult::ok(Star::new(center, tips as usize, outer, inner))
+}
+
+declare_shape_plugin!(c"star_shapes", [(c"star", create_star)]);
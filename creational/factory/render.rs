@@ -0,0 +1,48 @@
+//! Drawing primitives that shapes render themselves with, independent of the output format.
+
+use crate::transform::Transform;
+use crate::Point;
+
+/// Fill and stroke used when drawing a shape. `None` leaves that part unpainted.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Style {
+    pub fill: Option<String>,
+    pub stroke: Option<String>,
+    pub stroke_width: f64,
+}
+
+impl Style {
+    pub fn new() -> Style {
+        Style {
+            fill: None,
+            stroke: Some("black".to_string()),
+            stroke_width: 1.0,
+        }
+    }
+    pub fn fill(mut self, color: &str) -> Style {
+        self.fill = Some(color.to_string());
+        self
+    }
+    pub fn stroke(mut self, color: &str, width: f64) -> Style {
+        self.stroke = Some(color.to_string());
+        self.stroke_width = width;
+        self
+    }
+}
+
+impl Default for Style {
+    fn default() -> Style {
+        Style::new()
+    }
+}
+
+pub trait Renderer {
+    fn rect(&mut self, origin: Point, width: f64, height: f64, style: &Style);
+    fn circle(&mut self, center: Point, radius: f64, style: &Style);
+    fn ellipse(&mut self, center: Point, rx: f64, ry: f64, style: &Style);
+    fn polygon(&mut self, points: &[Point], style: &Style);
+    /// Draws everything up to the matching `pop_transform` through
+    /// `transform`, on top of any transform already pushed.
+    fn push_transform(&mut self, transform: &Transform);
+    fn pop_transform(&mut self);
+}
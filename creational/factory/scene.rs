@@ -0,0 +1,58 @@
+//! A scene collects styled shapes so they can be rendered together.
+
+use std::io::{self, Write};
+
+use crate::render::{Renderer, Style};
+use crate::svg::SvgDocument;
+use crate::{BoundingBox, Point, Shape};
+
+pub struct Scene {
+    items: Vec<(Box<dyn Shape>, Style)>,
+}
+
+impl Scene {
+    pub fn new() -> Scene {
+        Scene { items: Vec::new() }
+    }
+
+    pub fn add(&mut self, shape: Box<dyn Shape>, style: Style) {
+        self.items.push((shape, style));
+    }
+
+    pub fn shapes(&self) -> impl Iterator<Item = &dyn Shape> {
+        self.items.iter().map(|(shape, _)| shape.as_ref())
+    }
+
+    /// The box enclosing every shape, or `None` if there is nothing to
+    /// enclose, e.g. in an empty scene or one of only empty groups.
+    pub fn bounding_box(&self) -> Option<BoundingBox> {
+        self.shapes()
+            .map(|shape| shape.bounding_box())
+            .reduce(|a, b| a.union(&b))
+            .filter(|bbox| !bbox.is_empty())
+    }
+
+    /// Draws the shapes in insertion order.
+    pub fn render(&self, renderer: &mut dyn Renderer) {
+        for (shape, style) in &self.items {
+            shape.draw(renderer, style);
+        }
+    }
+
+    /// Writes the scene as a single SVG document sized to fit all shapes.
+    pub fn write_svg<W: Write>(&self, out: &mut W) -> io::Result<()> {
+        let mut doc = SvgDocument::new();
+        self.render(&mut doc);
+        let view = self.bounding_box().unwrap_or(BoundingBox {
+            min: Point::new(0.0, 0.0),
+            max: Point::new(0.0, 0.0),
+        });
+        doc.write_to(view, out)
+    }
+}
+
+impl Default for Scene {
+    fn default() -> Scene {
+        Scene::new()
+    }
+}
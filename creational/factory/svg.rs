@@ -0,0 +1,147 @@
+//! SVG backend for the `Renderer` trait.
+
+use std::io::{self, Write};
+
+use crate::render::{Renderer, Style};
+use crate::transform::Transform;
+use crate::{BoundingBox, Point};
+
+/// Collects drawn shapes as SVG elements. The output only depends on the
+/// drawing calls, so documents can be diffed between runs.
+pub struct SvgDocument {
+    elements: Vec<String>,
+    depth: usize,
+}
+
+impl SvgDocument {
+    pub fn new() -> SvgDocument {
+        SvgDocument {
+            elements: Vec::new(),
+            depth: 0,
+        }
+    }
+
+    /// Writes the complete document, using `view` as the visible area.
+    pub fn write_to<W: Write>(&self, view: BoundingBox, out: &mut W) -> io::Result<()> {
+        writeln!(
+            out,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="{} {} {} {}" width="{}" height="{}">"#,
+            num(view.min.x),
+            num(view.min.y),
+            num(view.width()),
+            num(view.height()),
+            num(view.width()),
+            num(view.height())
+        )?;
+        for element in &self.elements {
+            writeln!(out, "  {}", element)?;
+        }
+        writeln!(out, "</svg>")
+    }
+
+    fn push(&mut self, tag: &str, attrs: String, style: &Style) {
+        self.push_line(format!("<{} {} {}/>", tag, attrs, style_attrs(style)));
+    }
+
+    /// Adds a line indented to the current `<g>` nesting.
+    fn push_line(&mut self, line: String) {
+        self.elements
+            .push(format!("{}{}", "  ".repeat(self.depth), line));
+    }
+}
+
+impl Default for SvgDocument {
+    fn default() -> SvgDocument {
+        SvgDocument::new()
+    }
+}
+
+impl Renderer for SvgDocument {
+    fn rect(&mut self, origin: Point, width: f64, height: f64, style: &Style) {
+        let attrs = format!(
+            r#"x="{}" y="{}" width="{}" height="{}""#,
+            num(origin.x),
+            num(origin.y),
+            num(width),
+            num(height)
+        );
+        self.push("rect", attrs, style);
+    }
+    fn circle(&mut self, center: Point, radius: f64, style: &Style) {
+        let attrs = format!(
+            r#"cx="{}" cy="{}" r="{}""#,
+            num(center.x),
+            num(center.y),
+            num(radius)
+        );
+        self.push("circle", attrs, style);
+    }
+    fn ellipse(&mut self, center: Point, rx: f64, ry: f64, style: &Style) {
+        let attrs = format!(
+            r#"cx="{}" cy="{}" rx="{}" ry="{}""#,
+            num(center.x),
+            num(center.y),
+            num(rx),
+            num(ry)
+        );
+        self.push("ellipse", attrs, style);
+    }
+    fn polygon(&mut self, points: &[Point], style: &Style) {
+        let points: Vec<String> = points
+            .iter()
+            .map(|p| format!("{},{}", num(p.x), num(p.y)))
+            .collect();
+        self.push(
+            "polygon",
+            format!(r#"points="{}""#, points.join(" ")),
+            style,
+        );
+    }
+    fn push_transform(&mut self, t: &Transform) {
+        self.push_line(format!(
+            r#"<g transform="matrix({} {} {} {} {} {})">"#,
+            num(t.a),
+            num(t.b),
+            num(t.c),
+            num(t.d),
+            num(t.e),
+            num(t.f)
+        ));
+        self.depth += 1;
+    }
+    fn pop_transform(&mut self) {
+        self.depth = self.depth.saturating_sub(1);
+        self.push_line("</g>".to_string());
+    }
+}
+
+fn style_attrs(style: &Style) -> String {
+    let fill = style.fill.as_deref().unwrap_or("none");
+    match &style.stroke {
+        Some(stroke) => format!(
+            r#"fill="{}" stroke="{}" stroke-width="{}""#,
+            escape(fill),
+            escape(stroke),
+            num(style.stroke_width)
+        ),
+        None => format!(r#"fill="{}""#, escape(fill)),
+    }
+}
+
+/// Formats a coordinate with at most three decimals and no trailing zeros.
+fn num(v: f64) -> String {
+    let s = format!("{:.3}", v);
+    let s = s.trim_end_matches('0').trim_end_matches('.');
+    if s == "-0" {
+        "0".to_string()
+    } else {
+        s.to_string()
+    }
+}
+
+fn escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
@@ -0,0 +1,228 @@
+//! Affine transforms and group nodes, so shapes can be placed, scaled and
+//! rotated, and nested into a scene graph.
+
+use crate::collision::Collider;
+use crate::render::{Renderer, Style};
+use crate::{BoundingBox, Point, Shape};
+
+/// Maps `(x, y)` to `(a*x + c*y + e, b*x + d*y + f)`, the same layout as the
+/// SVG `matrix()` transform.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Transform {
+    pub a: f64,
+    pub b: f64,
+    pub c: f64,
+    pub d: f64,
+    pub e: f64,
+    pub f: f64,
+}
+
+impl Transform {
+    pub const IDENTITY: Transform = Transform {
+        a: 1.0,
+        b: 0.0,
+        c: 0.0,
+        d: 1.0,
+        e: 0.0,
+        f: 0.0,
+    };
+
+    pub fn translate(dx: f64, dy: f64) -> Transform {
+        Transform {
+            e: dx,
+            f: dy,
+            ..Transform::IDENTITY
+        }
+    }
+    pub fn scale(sx: f64, sy: f64) -> Transform {
+        Transform {
+            a: sx,
+            d: sy,
+            ..Transform::IDENTITY
+        }
+    }
+    /// Rotation around the origin, in radians.
+    pub fn rotate(angle: f64) -> Transform {
+        let (sin, cos) = angle.sin_cos();
+        Transform {
+            a: cos,
+            b: sin,
+            c: -sin,
+            d: cos,
+            ..Transform::IDENTITY
+        }
+    }
+
+    /// The transform applying `self` first and `next` after it.
+    pub fn then(&self, next: &Transform) -> Transform {
+        Transform {
+            a: next.a * self.a + next.c * self.b,
+            b: next.b * self.a + next.d * self.b,
+            c: next.a * self.c + next.c * self.d,
+            d: next.b * self.c + next.d * self.d,
+            e: next.a * self.e + next.c * self.f + next.e,
+            f: next.b * self.e + next.d * self.f + next.f,
+        }
+    }
+
+    pub fn apply(&self, p: Point) -> Point {
+        Point::new(
+            self.a * p.x + self.c * p.y + self.e,
+            self.b * p.x + self.d * p.y + self.f,
+        )
+    }
+
+    pub fn determinant(&self) -> f64 {
+        self.a * self.d - self.b * self.c
+    }
+
+    /// `None` when the transform collapses the plane, e.g. a zero scale.
+    pub fn inverse(&self) -> Option<Transform> {
+        let det = self.determinant();
+        if det.abs() < f64::EPSILON {
+            return None;
+        }
+        let (a, b, c, d) = (self.d / det, -self.b / det, -self.c / det, self.a / det);
+        Some(Transform {
+            a,
+            b,
+            c,
+            d,
+            e: -(a * self.e + c * self.f),
+            f: -(b * self.e + d * self.f),
+        })
+    }
+
+    /// Transforms the corners of `bbox` and returns the box around them.
+    fn apply_box(&self, bbox: &BoundingBox) -> BoundingBox {
+        if bbox.is_empty() {
+            return *bbox;
+        }
+        let corners = [
+            bbox.min,
+            Point::new(bbox.max.x, bbox.min.y),
+            bbox.max,
+            Point::new(bbox.min.x, bbox.max.y),
+        ];
+        let corners: Vec<Point> = corners.iter().map(|p| self.apply(*p)).collect();
+        BoundingBox::from_points(&corners)
+    }
+
+    /// How lengths change, exact for translations, rotations and uniform scales.
+    pub fn length_scale(&self) -> f64 {
+        self.determinant().abs().sqrt()
+    }
+
+    /// Whether the transform preserves shapes, i.e. only translates,
+    /// rotates, reflects and scales uniformly.
+    pub fn is_similarity(&self) -> bool {
+        let eq = |x: f64, y: f64| (x - y).abs() < 1e-9;
+        (eq(self.a, self.d) && eq(self.b, -self.c)) || (eq(self.a, -self.d) && eq(self.b, self.c))
+    }
+}
+
+impl Default for Transform {
+    fn default() -> Transform {
+        Transform::IDENTITY
+    }
+}
+
+/// Any shape drawn through a transform.
+///
+/// The bounding box is the box around the transformed corners of the
+/// shape's own box, so it may be loose for rotated curves. The perimeter is
+/// exact unless the transform scales non-uniformly.
+pub struct Transformed {
+    shape: Box<dyn Shape>,
+    transform: Transform,
+}
+
+impl Transformed {
+    pub fn new(shape: Box<dyn Shape>, transform: Transform) -> Transformed {
+        Transformed { shape, transform }
+    }
+}
+
+impl Shape for Transformed {
+    fn draw(&self, renderer: &mut dyn Renderer, style: &Style) {
+        renderer.push_transform(&self.transform);
+        self.shape.draw(renderer, style);
+        renderer.pop_transform();
+    }
+    fn area(&self) -> f64 {
+        self.shape.area() * self.transform.determinant().abs()
+    }
+    fn perimeter(&self) -> f64 {
+        self.shape.perimeter() * self.transform.length_scale()
+    }
+    fn bounding_box(&self) -> BoundingBox {
+        self.transform.apply_box(&self.shape.bounding_box())
+    }
+    fn contains(&self, p: Point) -> bool {
+        match self.transform.inverse() {
+            Some(inverse) => self.shape.contains(inverse.apply(p)),
+            None => false,
+        }
+    }
+    fn collider(&self) -> Collider {
+        self.shape.collider().transformed(&self.transform)
+    }
+}
+
+/// A scene graph node holding child shapes under a shared transform.
+/// Groups can be nested. Area and perimeter are the sums over the
+/// children, overlaps are not subtracted.
+pub struct Group {
+    transform: Transform,
+    children: Vec<Box<dyn Shape>>,
+}
+
+impl Group {
+    pub fn new(transform: Transform) -> Group {
+        Group {
+            transform,
+            children: Vec::new(),
+        }
+    }
+    pub fn add(mut self, child: Box<dyn Shape>) -> Group {
+        self.children.push(child);
+        self
+    }
+}
+
+impl Shape for Group {
+    fn draw(&self, renderer: &mut dyn Renderer, style: &Style) {
+        renderer.push_transform(&self.transform);
+        for child in &self.children {
+            child.draw(renderer, style);
+        }
+        renderer.pop_transform();
+    }
+    fn area(&self) -> f64 {
+        let area: f64 = self.children.iter().map(|c| c.area()).sum();
+        area * self.transform.determinant().abs()
+    }
+    fn perimeter(&self) -> f64 {
+        let perimeter: f64 = self.children.iter().map(|c| c.perimeter()).sum();
+        perimeter * self.transform.length_scale()
+    }
+    fn bounding_box(&self) -> BoundingBox {
+        self.children
+            .iter()
+            .map(|c| self.transform.apply_box(&c.bounding_box()))
+            .fold(BoundingBox::from_points(&[]), |a, b| a.union(&b))
+    }
+    fn contains(&self, p: Point) -> bool {
+        match self.transform.inverse() {
+            Some(inverse) => {
+                let local = inverse.apply(p);
+                self.children.iter().any(|c| c.contains(local))
+            }
+            None => false,
+        }
+    }
+    fn collider(&self) -> Collider {
+        let children = self.children.iter().map(|c| c.collider()).collect();
+        Collider::Compound(children).transformed(&self.transform)
+    }
+}
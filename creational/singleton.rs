@@ -1,22 +1,28 @@
-use std::mem::MaybeUninit;
-use std::sync::{Mutex, Once};
+#![forbid(unsafe_code)]
+
+#[path = "singleton/lazy.rs"]
+mod lazy;
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
+use std::time::Duration;
+
+use lazy::Singleton;
 
 #[derive(Debug)]
 struct Config {
     db_connection_str: String,
 }
 
-fn get_config() -> &'static Mutex<Config> {
-    static mut CONF: MaybeUninit<Mutex<Config>> = MaybeUninit::uninit();
-    static ONCE: Once = Once::new();
-
-    ONCE.call_once(|| unsafe {
-        CONF.as_mut_ptr().write(Mutex::new(Config {
-            db_connection_str: "test config".to_string(),
-        }));
-    });
+static CONF: Singleton<Mutex<Config>> = Singleton::new(|| {
+    Mutex::new(Config {
+        db_connection_str: "test config".to_string(),
+    })
+});
 
-    unsafe { &*CONF.as_ptr() }
+fn get_config() -> &'static Mutex<Config> {
+    CONF.instance()
 }
 
 fn main() {
@@ -32,5 +38,75 @@ fn main() {
     println!("{:?}", f2);
     let conf2 = f2.lock().unwrap();
 
-    assert_eq!(conf2.db_connection_str, "hello".to_string())
+    assert_eq!(conf2.db_connection_str, "hello".to_string());
+    drop(conf2);
+
+    // Threads racing to the first use still run the initializer once.
+    static INITS: AtomicUsize = AtomicUsize::new(0);
+    static SLOW: Singleton<String> = Singleton::new(|| {
+        INITS.fetch_add(1, Ordering::SeqCst);
+        thread::sleep(Duration::from_millis(20));
+        "slow".to_string()
+    });
+    let instances: Vec<&'static String> = thread::scope(|scope| {
+        let handles: Vec<_> = (0..8).map(|_| scope.spawn(|| SLOW.instance())).collect();
+        handles.into_iter().map(|h| h.join().unwrap()).collect()
+    });
+    assert_eq!(INITS.load(Ordering::SeqCst), 1);
+    assert!(instances.iter().all(|s| std::ptr::eq(*s, instances[0])));
+
+    // A failed initialization leaves the singleton unset for the next try.
+    static PORT: Singleton<u16> = Singleton::empty();
+    let port = PORT.get_or_try_init(|| "http".parse::<u16>());
+    if let Err(e) = port {
+        println!("{}", e); // output: invalid digit found in string
+    }
+    assert!(PORT.get().is_none());
+    assert_eq!(PORT.get_or_try_init(|| "8080".parse::<u16>()), Ok(&8080));
+    assert_eq!(PORT.get_or_init(|| 9090), &8080);
+
+    // Concurrent fallible initializations wait for each other: the first one
+    // fails, the second succeeds and the rest reuse its value.
+    static ATTEMPTS: AtomicUsize = AtomicUsize::new(0);
+    static TOKEN: Singleton<String> = Singleton::empty();
+    let results: Vec<Result<&'static String, String>> = thread::scope(|scope| {
+        let handles: Vec<_> = (0..8)
+            .map(|_| {
+                scope.spawn(|| {
+                    TOKEN.get_or_try_init(|| {
+                        thread::sleep(Duration::from_millis(5));
+                        match ATTEMPTS.fetch_add(1, Ordering::SeqCst) {
+                            0 => Err("token server unavailable".to_string()),
+                            n => Ok(format!("token-{}", n)),
+                        }
+                    })
+                })
+            })
+            .collect();
+        handles.into_iter().map(|h| h.join().unwrap()).collect()
+    });
+    assert_eq!(ATTEMPTS.load(Ordering::SeqCst), 2);
+    assert_eq!(results.iter().filter(|r| r.is_err()).count(), 1);
+    assert!(results
+        .iter()
+        .flatten()
+        .all(|token| token.as_str() == "token-1"));
+    assert_eq!(TOKEN.instance(), "token-1");
+
+    // Fallible and infallible initializations wait for each other too.
+    static RUNS: AtomicUsize = AtomicUsize::new(0);
+    static MIXED: Singleton<usize> = Singleton::empty();
+    let init = || {
+        thread::sleep(Duration::from_millis(5));
+        RUNS.fetch_add(1, Ordering::SeqCst)
+    };
+    thread::scope(|scope| {
+        for i in 0..8 {
+            scope.spawn(move || match i % 2 {
+                0 => *MIXED.get_or_init(init),
+                _ => *MIXED.get_or_try_init(|| Ok::<_, ()>(init())).unwrap(),
+            });
+        }
+    });
+    assert_eq!(RUNS.load(Ordering::SeqCst), 1);
 }
@@ -0,0 +1,91 @@
+//! A lazily initialized global without `unsafe`, declared as a `static`:
+//!
+//! ```ignore
+//! static CONFIG: Singleton<Mutex<Config>> = Singleton::new(|| Mutex::new(Config::load()));
+//!
+//! let config = CONFIG.instance();
+//! ```
+//!
+//! The initializer runs once, on first use, even when several threads get
+//! there at the same time. A singleton declared with `Singleton::empty()` has
+//! no initializer and is set by the first `get_or_init` or `get_or_try_init`
+//! call instead.
+
+use std::sync::{Mutex, MutexGuard, OnceLock};
+
+pub struct Singleton<T> {
+    value: OnceLock<T>,
+    init: Option<fn() -> T>,
+    /// Serializes all initializations, as `OnceLock` cannot make a fallible
+    /// one wait for an infallible one.
+    init_lock: Mutex<()>,
+}
+
+impl<T> Singleton<T> {
+    pub const fn new(init: fn() -> T) -> Singleton<T> {
+        Singleton {
+            value: OnceLock::new(),
+            init: Some(init),
+            init_lock: Mutex::new(()),
+        }
+    }
+
+    pub const fn empty() -> Singleton<T> {
+        Singleton {
+            value: OnceLock::new(),
+            init: None,
+            init_lock: Mutex::new(()),
+        }
+    }
+
+    /// The value, if it was initialized already.
+    pub fn get(&self) -> Option<&T> {
+        self.value.get()
+    }
+
+    /// The value, initialized by the initializer of the singleton if needed.
+    ///
+    /// Panics if the singleton has no initializer and was not set yet.
+    pub fn instance(&self) -> &T {
+        match self.init {
+            Some(init) => self.get_or_init(init),
+            None => self
+                .get()
+                .expect("singleton without initializer used before it was set"),
+        }
+    }
+
+    pub fn get_or_init<F: FnOnce() -> T>(&self, init: F) -> &T {
+        if let Some(value) = self.value.get() {
+            return value;
+        }
+        let _guard = self.lock();
+        self.value.get_or_init(init)
+    }
+
+    /// Like `get_or_init`, but an error leaves the singleton unset so that a
+    /// later call can try again. Concurrent callers wait for the running
+    /// attempt instead of starting their own.
+    pub fn get_or_try_init<F, E>(&self, init: F) -> Result<&T, E>
+    where
+        F: FnOnce() -> Result<T, E>,
+    {
+        if let Some(value) = self.value.get() {
+            return Ok(value);
+        }
+        let _guard = self.lock();
+        if let Some(value) = self.value.get() {
+            return Ok(value);
+        }
+        let value = init()?;
+        Ok(self.value.get_or_init(|| value))
+    }
+
+    fn lock(&self) -> MutexGuard<'_, ()> {
+        // A panicking initializer poisons the lock but leaves nothing half
+        // initialized, so the next attempt can go ahead.
+        self.init_lock
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
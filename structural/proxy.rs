@@ -23,7 +23,7 @@ struct Proxy<'a> {
     real_subject: &'a RealSubject,
 }
 impl<'a> Proxy<'a> {
-    fn new(real_subject: &'a RealSubject) -> Proxy {
+    fn new(real_subject: &'a RealSubject) -> Proxy<'a> {
         Proxy { real_subject }
     }
     fn check_access(&self) -> bool {
@@ -66,7 +66,7 @@ fn main() {
     println!("client: executing the client code with a real subject:");
     Client::client_code(&real_subject);
 
-    println!("");
+    println!();
     println!("client: executing the same client code with a proxy:");
     let proxy = Proxy::new(&real_subject);
     Client::client_code(&proxy);